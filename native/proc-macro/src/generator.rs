@@ -4,6 +4,7 @@ use quote::quote;
 pub fn module(attr: TokenStream2, item: TokenStream2) -> Result<TokenStream2, syn::Error> {
     let mut bindings: Vec<TokenStream2> = vec![];
     let mut modules: Vec<TokenStream2> = vec![];
+    let mut signatures: Vec<TokenStream2> = vec![];
 
     let env = attr.to_string();
 
@@ -17,6 +18,21 @@ pub fn module(attr: TokenStream2, item: TokenStream2) -> Result<TokenStream2, sy
 
                 let mut bindings_inputs: Vec<TokenStream2> = vec![];
                 let mut modules_inputs: Vec<TokenStream2> = vec![];
+                let mut modules_input_types: Vec<TokenStream2> = vec![];
+
+                let cost = parse_cost(&func)?;
+                // Lengths of the data passed to the function, used to charge fuel per byte
+                let lengths: Vec<TokenStream2> = cost
+                    .bytes
+                    .iter()
+                    .map(|arg_name| quote!(#arg_name as u64))
+                    .collect();
+                // Number of entries requested from the node, used to charge fuel per entry
+                let entries: Vec<TokenStream2> = cost
+                    .entries
+                    .iter()
+                    .map(|arg_name| quote!(#arg_name as u64))
+                    .collect();
 
                 for arg in func.sig.inputs.iter() {
                     if let syn::FnArg::Typed(a) = arg {
@@ -33,6 +49,7 @@ pub fn module(attr: TokenStream2, item: TokenStream2) -> Result<TokenStream2, sy
                                 modules_inputs.push(quote!(
                                     #arg_name: #type_string
                                 ));
+                                modules_input_types.push(type_string);
                             }
                         }
                     }
@@ -63,15 +80,26 @@ pub fn module(attr: TokenStream2, item: TokenStream2) -> Result<TokenStream2, sy
                     fn #func_name(store: &mut Store<Runtime>) -> (String, String, Func) {
                         (#module.to_string(), #name.to_string(), Func::wrap(
                             store,
-                            |caller: Caller<Runtime>, #( #modules_inputs ),* | -> ( #( #modules_output ),* ) {
+                            |mut caller: Caller<Runtime>, #( #modules_inputs ),* | -> Result<( #( #modules_output ),* ), Trap> {
+                                let bytes: u64 = 0 #( + #lengths )*;
+                                let entries: u64 = 0 #( + #entries )*;
+                                env::charge_fuel(&mut caller, #module, #name, bytes, entries)?;
+
                                 let func = #func_block;
-                                func(caller)
+                                Ok(func(caller))
                             }
                         ))
                     }
 
                     vec.push(#func_name);
                 ));
+
+                signatures.push(quote!(
+                    (#module.to_string(), #name.to_string(), FuncType::new(
+                        <( #( #modules_input_types ),* ) as WasmTypeList>::types(),
+                        <( #( #modules_output ),* ) as WasmTypeList>::types(),
+                    ))
+                ));
             }
         }
     }
@@ -87,8 +115,8 @@ pub fn module(attr: TokenStream2, item: TokenStream2) -> Result<TokenStream2, sy
 
         #[cfg(not(feature = "bindings"))]
         pub mod modules {
-            use crate::{env, error::{Error, RuntimeError}, modules::Module, runtime::Runtime};
-            use wasmi::{Caller, Func, Store};
+            use crate::{env, error::{Error, RuntimeError}, modules::{Module, Signature}, runtime::{data_entry, Runtime}};
+            use wasmi::{core::Trap, Caller, Func, FuncType, Store, WasmTypeList};
 
             pub fn modules() -> Vec<Module> {
                 let mut vec: Vec<Module> = vec![];
@@ -97,10 +125,74 @@ pub fn module(attr: TokenStream2, item: TokenStream2) -> Result<TokenStream2, sy
 
                 vec
             }
+
+            /// Signatures of the host functions as `(module, name, type)`.
+            pub fn signatures() -> Vec<Signature> {
+                vec![ #( #signatures ),* ]
+            }
         }
     ))
 }
 
+/// Arguments of a host function that the charged fuel depends on.
+#[derive(Default)]
+struct Cost {
+    bytes: Vec<syn::Ident>,
+    entries: Vec<syn::Ident>,
+}
+
+/// Parses `#[cost(bytes = "length_a, length_b", entries = "limit")]` of a host function.
+fn parse_cost(func: &syn::ItemFn) -> Result<Cost, syn::Error> {
+    let mut cost = Cost::default();
+
+    let arg_names: Vec<&syn::Ident> = func
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(a) => match &*a.pat {
+                syn::Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    for attr in func
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cost"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let dimension = if meta.path.is_ident("bytes") {
+                &mut cost.bytes
+            } else if meta.path.is_ident("entries") {
+                &mut cost.entries
+            } else {
+                return Err(meta.error("expected `bytes` or `entries`"));
+            };
+
+            let value: syn::LitStr = meta.value()?.parse()?;
+
+            for name in value.value().split(',').map(str::trim) {
+                match arg_names.iter().find(|arg_name| **arg_name == name) {
+                    Some(arg_name) => dimension.push((*arg_name).clone()),
+                    None => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!("`{}` has no argument `{}`", func.sig.ident, name),
+                        ))
+                    }
+                }
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(cost)
+}
+
 fn parse_type(type_: &syn::Type, is_bindings: bool) -> Option<TokenStream2> {
     match (type_, is_bindings) {
         (syn::Type::Ptr(_), true) => Some(quote!(*const u8)),
@@ -6,6 +6,6 @@ use proc_macro::TokenStream;
 pub fn module(attr: TokenStream, item: TokenStream) -> TokenStream {
     match generator::module(attr.into(), item.into()) {
         Ok(result) => result.into(),
-        Err(_) => panic!(),
+        Err(err) => err.to_compile_error().into(),
     }
 }
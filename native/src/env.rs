@@ -1,3 +1,15 @@
+/// Reading a slice of WASM linear memory with bounds checking.
+/// If the slice is out of bounds, returns from the host function
+/// with the error code followed by the given default values.
+macro_rules! memory_slice {
+    ($memory:expr, $offset:expr, $length:expr $(, $default:expr)*) => {
+        match crate::env::read_memory($memory, $offset, $length) {
+            Ok(bytes) => bytes,
+            Err(error) => return (error.as_i32() $(, $default)*),
+        }
+    };
+}
+
 pub mod asset;
 pub mod block;
 pub mod call_contract;
@@ -10,13 +22,15 @@ pub mod tx;
 pub mod utils;
 
 use crate::{
-    error::Result,
+    error::{Error, ExecutableError, Result, RuntimeError},
     node::Node,
     runtime::{
+        self,
         asset_holder::{AddressVersion, AssetHolder, Type},
         Runtime,
     },
 };
+use wasmi::{core::TrapCode, Caller};
 
 pub enum Field {
     String(String),
@@ -35,19 +49,103 @@ pub(in crate::env) fn get_asset_holder(
     Ok(AssetHolder::from_bytes(type_, version, chain_id, bytes).as_bytes())
 }
 
+/// Maximum number of entries returned by a host function,
+/// since the lists written to memory are prefixed with the `u16` count.
+pub const MAX_ENTRIES: u32 = u16::MAX as u32;
+
+/// Charging fuel for a host function call according to the cost schedule of the VM.
+/// `bytes` is the total length of the data passed to the call, `entries` is the number
+/// of entries requested from the node, charged before the node is queried.
+///
+/// The output of the call is charged by [`write_memory`] with the same per-byte cost.
+///
+/// After the deadline of the VM the remaining fuel is consumed, so the call traps
+/// and the callers in the call stack stop at their next instruction.
+pub fn charge_fuel(
+    caller: &mut Caller<Runtime>,
+    module: &str,
+    name: &str,
+    bytes: u64,
+    entries: u64,
+) -> Result<(), TrapCode> {
+    if caller.data().vm.is_timed_out() {
+        exhaust_fuel(caller);
+        return Err(TrapCode::OutOfFuel);
+    }
+
+    let schedule = caller.data().vm.cost_schedule();
+    let fuel = schedule
+        .fuel(module, name, bytes)
+        .saturating_add(schedule.entries_fuel(module, name, entries.min(MAX_ENTRIES as u64)));
+    let output_cost = schedule.get(module, name).per_byte;
+    caller.data_mut().set_output_cost(output_cost);
+
+    if fuel > 0 {
+        caller.consume_fuel(fuel).map_err(|_| TrapCode::OutOfFuel)?;
+    }
+
+    Ok(())
+}
+
+/// Consuming the remaining fuel, so the contract traps at its next instruction.
+fn exhaust_fuel(caller: &mut Caller<Runtime>) {
+    if let Ok(remaining) = caller.consume_fuel(0) {
+        let _ = caller.consume_fuel(remaining);
+    }
+}
+
+/// Getting a slice of WASM linear memory.
+/// Offset and length are passed by the contract, so they are checked against the memory size.
+pub(in crate::env) fn read_memory(memory: &[u8], offset: u32, length: u32) -> Result<&[u8]> {
+    let offset = offset as usize;
+
+    offset
+        .checked_add(length as usize)
+        .and_then(|end| memory.get(offset..end))
+        .ok_or(Error::Runtime(RuntimeError::MemoryOutOfBounds))
+}
+
 /// Wrapper over writing to WASM linear memory.
+/// The data is written to a region allocated by [`runtime::allocate`].
+/// Each byte is charged with the per-byte cost of the called host function,
+/// if the fuel is not enough it is exhausted and the error code is returned.
 /// Functions using this wrapper return (i32, i32, i32):
 /// * First value - error code
 /// * Second value - memory offset
 /// * Third value - length of data in memory
 pub(in crate::env) fn write_memory(
-    ctx: &mut Runtime,
-    memory: &mut [u8],
-    offset_memory: usize,
+    caller: &mut Caller<Runtime>,
     result: Vec<u8>,
 ) -> (i32, u32, u32) {
     let length = result.len();
-    memory[offset_memory..offset_memory + length].copy_from_slice(result.as_slice());
-    ctx.set_heap_base((offset_memory + length) as i32);
+
+    let fuel = caller.data().output_cost().saturating_mul(length as u64);
+    if let Err(error) = caller.consume_fuel(fuel) {
+        exhaust_fuel(caller);
+        return (
+            ExecutableError::FailedExec(format!("{:?}", error)).as_i32(),
+            0,
+            0,
+        );
+    }
+
+    let offset_memory = match runtime::allocate(&mut *caller, length) {
+        Ok(offset) => offset,
+        Err(error) => return (error.as_i32(), 0, 0),
+    };
+
+    let memory = match caller.data().memory() {
+        Some(memory) => memory.data_mut(&mut *caller),
+        None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
+    };
+
+    match offset_memory
+        .checked_add(length)
+        .and_then(|end| memory.get_mut(offset_memory..end))
+    {
+        Some(bytes) => bytes.copy_from_slice(result.as_slice()),
+        None => return (RuntimeError::MemoryOutOfBounds.as_i32(), 0, 0),
+    }
+
     (0, offset_memory as u32, length as u32)
 }
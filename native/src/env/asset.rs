@@ -16,12 +16,10 @@ pub fn get_balance(
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
     };
 
-    let asset_id =
-        &memory[offset_asset_id as usize..offset_asset_id as usize + length_asset_id as usize];
+    let asset_id = memory_slice!(memory, offset_asset_id, length_asset_id, 0);
 
     let (type_, bytes) = if length_asset_holder != 0 {
-        let bytes = &memory[offset_asset_holder as usize
-            ..offset_asset_holder as usize + length_asset_holder as usize];
+        let bytes = memory_slice!(memory, offset_asset_holder, length_asset_holder, 0);
         (type_, bytes.to_vec())
     } else {
         (1, ctx.vm.top_frame().contract_id())
@@ -41,6 +39,7 @@ pub fn get_balance(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn transfer(
     offset_asset_id: u32,
     length_asset_id: u32,
@@ -57,11 +56,9 @@ pub fn transfer(
     };
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let asset_id =
-        &memory[offset_asset_id as usize..offset_asset_id as usize + length_asset_id as usize];
+    let asset_id = memory_slice!(memory, offset_asset_id, length_asset_id);
 
-    let recipient =
-        &memory[offset_recipient as usize..offset_recipient as usize + length_recipient as usize];
+    let recipient = memory_slice!(memory, offset_recipient, length_recipient);
     let asset_holder = match crate::env::get_asset_holder(ctx, type_, version, recipient.to_vec()) {
         Ok(bytes) => bytes,
         Err(error) => {
@@ -84,6 +81,7 @@ pub fn transfer(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn issue(
     offset_name: u32,
     length_name: u32,
@@ -98,12 +96,10 @@ pub fn issue(
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let name = &memory[offset_name as usize..offset_name as usize + length_name as usize];
-    let description = &memory
-        [offset_description as usize..offset_description as usize + length_description as usize];
+    let name = memory_slice!(memory, offset_name, length_name, 0, 0);
+    let description = memory_slice!(memory, offset_description, length_description, 0, 0);
 
     match ctx.vm.issue(
         contract_id.as_slice(),
@@ -113,7 +109,7 @@ pub fn issue(
         decimals,
         is_reissuable != 0,
     ) {
-        Ok(result) => crate::env::write_memory(ctx, memory, offset_memory, result),
+        Ok(result) => crate::env::write_memory(&mut caller, result),
         Err(error) => {
             error!("{}", error);
             (error.as_i32(), 0, 0)
@@ -135,8 +131,7 @@ pub fn burn(
     };
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let asset_id =
-        &memory[offset_asset_id as usize..offset_asset_id as usize + length_asset_id as usize];
+    let asset_id = memory_slice!(memory, offset_asset_id, length_asset_id);
 
     match ctx.vm.burn(contract_id.as_slice(), asset_id, amount) {
         Ok(_) => 0,
@@ -160,8 +155,7 @@ pub fn reissue(
     };
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let asset_id =
-        &memory[offset_asset_id as usize..offset_asset_id as usize + length_asset_id as usize];
+    let asset_id = memory_slice!(memory, offset_asset_id, length_asset_id);
 
     match ctx
         .vm
@@ -10,17 +10,14 @@ pub fn get_block_field(field: Field, mut caller: Caller<Runtime>) -> (i32, i64)
 
     let field = match field {
         Field::String(name) => name.into_bytes(),
-        Field::Binary(offset, length) => {
-            memory[offset as usize..offset as usize + length as usize].to_vec()
-        }
+        Field::Binary(offset, length) => memory_slice!(memory, offset, length, 0).to_vec(),
     };
 
     match ctx.vm.block(field.as_slice()) {
-        Ok(bytes) => {
-            let mut result = [0u8; 8];
-            result.copy_from_slice(&bytes);
-            (0, i64::from_be_bytes(result))
-        }
+        Ok(bytes) => match bytes.as_slice().try_into() {
+            Ok(bytes) => (0, i64::from_be_bytes(bytes)),
+            Err(_) => (RuntimeError::ConvertingNumericTypes.as_i32(), 0),
+        },
         Err(error) => {
             error!("{}", error);
             (error.as_i32(), 0)
@@ -33,17 +30,14 @@ pub fn block(field: Field, mut caller: Caller<Runtime>) -> (i32, u32, u32) {
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
     let field = match field {
         Field::String(name) => name.into_bytes(),
-        Field::Binary(offset, length) => {
-            memory[offset as usize..offset as usize + length as usize].to_vec()
-        }
+        Field::Binary(offset, length) => memory_slice!(memory, offset, length, 0, 0).to_vec(),
     };
 
     match ctx.vm.block(field.as_slice()) {
-        Ok(result) => crate::env::write_memory(ctx, memory, offset_memory, result),
+        Ok(result) => crate::env::write_memory(&mut caller, result),
         Err(error) => {
             error!("{}", error);
             (error.as_i32(), 0, 0)
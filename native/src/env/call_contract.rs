@@ -1,7 +1,7 @@
 use crate::{
-    error::RuntimeError,
+    error::{ExecutableError, RuntimeError},
     node::Node,
-    runtime::{data_entry::DataEntry, payment_id::PaymentId, Runtime},
+    runtime::{data_entry::DataEntry, Runtime},
 };
 use log::error;
 use std::str;
@@ -21,7 +21,7 @@ pub fn call_arg_binary(offset_value: u32, length_value: u32, mut caller: Caller<
         None => return RuntimeError::MemoryNotFound.as_i32(),
     };
 
-    let value = &memory[offset_value as usize..offset_value as usize + length_value as usize];
+    let value = memory_slice!(memory, offset_value, length_value);
     ctx.params.push(DataEntry::Binary(value.to_vec()));
 
     0
@@ -33,12 +33,40 @@ pub fn call_arg_string(offset_value: u32, length_value: u32, mut caller: Caller<
         None => return RuntimeError::MemoryNotFound.as_i32(),
     };
 
-    let value = &memory[offset_value as usize..offset_value as usize + length_value as usize];
+    let value = memory_slice!(memory, offset_value, length_value);
     ctx.params.push(DataEntry::String(value.to_vec()));
 
     0
 }
 
+pub fn call_arg_uint(value: i64, mut caller: Caller<Runtime>) {
+    caller
+        .data_mut()
+        .params
+        .push(DataEntry::UnsignedInteger(value as u64));
+}
+
+/// Adding the argument of the type given by the tag, written to memory as in `DataEntry::into_tagged`.
+pub fn call_arg_tagged(
+    tag: u8,
+    offset_value: u32,
+    length_value: u32,
+    mut caller: Caller<Runtime>,
+) -> i32 {
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut caller),
+        None => return RuntimeError::MemoryNotFound.as_i32(),
+    };
+
+    let value = memory_slice!(memory, offset_value, length_value);
+    match DataEntry::from_tagged(tag, value) {
+        Ok(value) => ctx.params.push(value),
+        Err(error) => return error.as_i32(),
+    }
+
+    0
+}
+
 pub fn call_payment(
     offset_asset_id: u32,
     length_asset_id: u32,
@@ -50,8 +78,7 @@ pub fn call_payment(
         None => return RuntimeError::MemoryNotFound.as_i32(),
     };
 
-    let asset_id =
-        &memory[offset_asset_id as usize..offset_asset_id as usize + length_asset_id as usize];
+    let asset_id = memory_slice!(memory, offset_asset_id, length_asset_id);
     ctx.payments.push(asset_id, amount);
 
     0
@@ -66,33 +93,105 @@ pub fn call_contract(
     length_params: Option<u32>,
     mut caller: Caller<Runtime>,
 ) -> i32 {
+    let (result, _) = call(
+        offset_contract_id,
+        length_contract_id,
+        offset_func_name,
+        length_func_name,
+        offset_params,
+        length_params,
+        &mut caller,
+    );
+
+    result
+}
+
+/// Calling the contract and writing the data it returned to memory.
+pub fn call_contract_return(
+    offset_contract_id: u32,
+    length_contract_id: u32,
+    offset_func_name: u32,
+    length_func_name: u32,
+    offset_params: Option<u32>,
+    length_params: Option<u32>,
+    mut caller: Caller<Runtime>,
+) -> (i32, u32, u32) {
+    match call(
+        offset_contract_id,
+        length_contract_id,
+        offset_func_name,
+        length_func_name,
+        offset_params,
+        length_params,
+        &mut caller,
+    ) {
+        (0, return_data) => crate::env::write_memory(&mut caller, return_data),
+        (error, _) => (error, 0, 0),
+    }
+}
+
+pub fn set_return(offset_value: u32, length_value: u32, mut caller: Caller<Runtime>) -> i32 {
     let (memory, ctx) = match caller.data().memory() {
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return RuntimeError::MemoryNotFound.as_i32(),
     };
 
-    let callable_contract_id = &memory
-        [offset_contract_id as usize..offset_contract_id as usize + length_contract_id as usize];
+    let value = memory_slice!(memory, offset_value, length_value);
+    ctx.vm.set_return_data(value.to_vec());
+
+    0
+}
+
+/// Calling the contract.
+/// Returns the error code returned by the contract and the data it returned.
+fn call(
+    offset_contract_id: u32,
+    length_contract_id: u32,
+    offset_func_name: u32,
+    length_func_name: u32,
+    offset_params: Option<u32>,
+    length_params: Option<u32>,
+    caller: &mut Caller<Runtime>,
+) -> (i32, Vec<u8>) {
+    // The callee can only spend the fuel that the caller has left
+    let fuel = match caller.consume_fuel(0) {
+        Ok(fuel) => fuel,
+        Err(error) => {
+            return (
+                ExecutableError::FuelMeteringDisabled(format!("{:?}", error)).as_i32(),
+                vec![],
+            )
+        }
+    };
+
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut *caller),
+        None => return (RuntimeError::MemoryNotFound.as_i32(), vec![]),
+    };
+
+    let callable_contract_id =
+        memory_slice!(memory, offset_contract_id, length_contract_id, vec![]);
 
     let bytecode = match ctx.vm.get_bytecode(callable_contract_id) {
         Ok(bytecode) => bytecode,
         Err(error) => {
             error!("{}", error);
-            return error.as_i32();
+            return (error.as_i32(), vec![]);
         }
     };
 
-    let func_name = match str::from_utf8(
-        &memory[offset_func_name as usize..offset_func_name as usize + length_func_name as usize],
-    ) {
+    let func_name = match str::from_utf8(memory_slice!(
+        memory,
+        offset_func_name,
+        length_func_name,
+        vec![]
+    )) {
         Ok(string) => string,
-        Err(_) => return RuntimeError::Utf8Error.as_i32(),
+        Err(_) => return (RuntimeError::Utf8Error.as_i32(), vec![]),
     };
 
     let params: Vec<u8> = match (offset_params, length_params) {
-        (Some(offset), Some(length)) => {
-            memory[offset as usize..offset as usize + length as usize].to_vec()
-        }
+        (Some(offset), Some(length)) => memory_slice!(memory, offset, length, vec![]).to_vec(),
         _ => {
             let bytes = ctx.params.as_bytes();
             ctx.params.reset();
@@ -103,50 +202,51 @@ pub fn call_contract(
     // Since a single contract can be invoked multiple times during execution,
     // it is necessary to have a unique identifier to distinguish each unique execution
     let nonce = ctx.vm.get_nonce();
-    let payment_id = PaymentId::new(callable_contract_id.to_vec(), nonce);
 
-    let self_contract_id = ctx.vm.top_frame().contract_id();
+    let payments = match ctx.payments.is_empty() {
+        true => vec![],
+        false => ctx.payments.as_bytes(),
+    };
+    ctx.payments.reset();
 
-    if !ctx.payments.is_empty() {
-        let payments = ctx.payments.as_bytes();
-        ctx.payments.reset();
+    ctx.vm.set_fuel(fuel);
 
-        match ctx.vm.add_payments(
-            self_contract_id.as_slice(),
-            payment_id.as_bytes().as_slice(),
-            &payments,
-        ) {
-            Ok(()) => (),
-            Err(error) => {
-                error!("{}", error);
-                return error.as_i32();
-            }
-        }
-    }
-
-    match ctx.vm.call(
+    // Payments are made within the call, so they are discarded if the call fails
+    let result = ctx.vm.call(
         callable_contract_id.to_vec(),
         bytecode,
         nonce,
         func_name,
         &params,
-    ) {
-        Ok(result) => {
-            // TODO: Functions cannot return any values, they can only return an error code
+        &payments,
+    );
+
+    // Charge the caller for the fuel spent by the callee
+    let fuel_spent = fuel.saturating_sub(ctx.vm.fuel());
+    if let Err(error) = caller.consume_fuel(fuel_spent) {
+        return (
+            ExecutableError::FuelMeteringDisabled(format!("{:?}", error)).as_i32(),
+            vec![],
+        );
+    }
+
+    match result {
+        Ok((result, return_data)) => {
+            // Functions only return an error code, data is returned with `set_return`
             let error = RuntimeError::InvalidResult(format!("Functions cannot return any values, they can only return an error code. Result: {:?}", result));
             if result.len() != 1 {
                 error!("{}", error);
-                return error.as_i32();
+                return (error.as_i32(), vec![]);
             }
 
             match result[0] {
-                Value::I32(value) => value,
-                _ => error.as_i32(),
+                Value::I32(value) => (value, return_data),
+                _ => (error.as_i32(), vec![]),
             }
         }
         Err(error) => {
             error!("{}", error);
-            error.as_i32()
+            (error.as_i32(), vec![])
         }
     }
 }
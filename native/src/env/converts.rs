@@ -8,7 +8,7 @@ pub fn parse_int(offset: u32, length: u32, mut caller: Caller<Runtime>) -> (i32,
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
     };
 
-    let string = match str::from_utf8(&memory[offset as usize..offset as usize + length as usize]) {
+    let string = match str::from_utf8(memory_slice!(memory, offset, length, 0)) {
         Ok(value) => value,
         Err(_) => return (RuntimeError::Utf8Error.as_i32(), 0),
     };
@@ -25,7 +25,7 @@ pub fn parse_bool(offset: u32, length: u32, mut caller: Caller<Runtime>) -> (i32
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
     };
 
-    let string = match str::from_utf8(&memory[offset as usize..offset as usize + length as usize]) {
+    let string = match str::from_utf8(memory_slice!(memory, offset, length, 0)) {
         Ok(value) => value,
         Err(_) => return (RuntimeError::Utf8Error.as_i32(), 0),
     };
@@ -37,14 +37,8 @@ pub fn parse_bool(offset: u32, length: u32, mut caller: Caller<Runtime>) -> (i32
 }
 
 pub fn to_bytes(value: i64, mut caller: Caller<Runtime>) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
-        Some(memory) => memory.data_and_store_mut(&mut caller),
-        None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
-    };
-    let offset_memory = ctx.heap_base() as usize;
-
     let result = value.to_be_bytes().to_vec();
-    crate::env::write_memory(ctx, memory, offset_memory, result)
+    crate::env::write_memory(&mut caller, result)
 }
 
 pub fn to_int(offset: u32, length: u32, mut caller: Caller<Runtime>) -> (i32, i64) {
@@ -53,19 +47,13 @@ pub fn to_int(offset: u32, length: u32, mut caller: Caller<Runtime>) -> (i32, i6
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
     };
 
-    let mut bytes = [0u8; 8];
-    bytes.copy_from_slice(&memory[offset as usize..offset as usize + length as usize]);
-
-    (0, i64::from_be_bytes(bytes))
+    match memory_slice!(memory, offset, length, 0).try_into() {
+        Ok(bytes) => (0, i64::from_be_bytes(bytes)),
+        Err(_) => (RuntimeError::ConvertingNumericTypes.as_i32(), 0),
+    }
 }
 
 pub fn to_string<T: Display>(value: T, mut caller: Caller<Runtime>) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
-        Some(memory) => memory.data_and_store_mut(&mut caller),
-        None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
-    };
-    let offset_memory = ctx.heap_base() as usize;
-
     let result = value.to_string().into_bytes();
-    crate::env::write_memory(ctx, memory, offset_memory, result)
+    crate::env::write_memory(&mut caller, result)
 }
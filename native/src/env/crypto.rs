@@ -13,12 +13,11 @@ pub fn fast_hash(
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
-    let bytes = &memory[offset_bytes as usize..offset_bytes as usize + length_bytes as usize];
+    let bytes = memory_slice!(memory, offset_bytes, length_bytes, 0, 0);
 
     match ctx.vm.fast_hash(bytes) {
-        Ok(result) => crate::env::write_memory(ctx, memory, offset_memory, result),
+        Ok(result) => crate::env::write_memory(&mut caller, result),
         Err(error) => (error.as_i32(), 0, 0),
     }
 }
@@ -32,12 +31,11 @@ pub fn secure_hash(
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
-    let bytes = &memory[offset_bytes as usize..offset_bytes as usize + length_bytes as usize];
+    let bytes = memory_slice!(memory, offset_bytes, length_bytes, 0, 0);
 
     match ctx.vm.secure_hash(bytes) {
-        Ok(result) => crate::env::write_memory(ctx, memory, offset_memory, result),
+        Ok(result) => crate::env::write_memory(&mut caller, result),
         Err(error) => (error.as_i32(), 0, 0),
     }
 }
@@ -47,16 +45,15 @@ pub fn blake2b256(
     length_bytes: u32,
     mut caller: Caller<Runtime>,
 ) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
+    let (memory, _) = match caller.data().memory() {
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
     let mut hasher: Blake2b<U32> = Blake2b::new();
-    hasher.update(&memory[offset_bytes as usize..offset_bytes as usize + length_bytes as usize]);
+    hasher.update(memory_slice!(memory, offset_bytes, length_bytes, 0, 0));
 
-    crate::env::write_memory(ctx, memory, offset_memory, hasher.finalize().to_vec())
+    crate::env::write_memory(&mut caller, hasher.finalize().to_vec())
 }
 
 pub fn keccak256(
@@ -64,16 +61,15 @@ pub fn keccak256(
     length_bytes: u32,
     mut caller: Caller<Runtime>,
 ) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
+    let (memory, _) = match caller.data().memory() {
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
     let mut hasher = Keccak256::new();
-    hasher.update(&memory[offset_bytes as usize..offset_bytes as usize + length_bytes as usize]);
+    hasher.update(memory_slice!(memory, offset_bytes, length_bytes, 0, 0));
 
-    crate::env::write_memory(ctx, memory, offset_memory, hasher.finalize().to_vec())
+    crate::env::write_memory(&mut caller, hasher.finalize().to_vec())
 }
 
 pub fn sha256(
@@ -81,16 +77,15 @@ pub fn sha256(
     length_bytes: u32,
     mut caller: Caller<Runtime>,
 ) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
+    let (memory, _) = match caller.data().memory() {
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
     let mut hasher = Sha256::new();
-    hasher.update(&memory[offset_bytes as usize..offset_bytes as usize + length_bytes as usize]);
+    hasher.update(memory_slice!(memory, offset_bytes, length_bytes, 0, 0));
 
-    crate::env::write_memory(ctx, memory, offset_memory, hasher.finalize().to_vec())
+    crate::env::write_memory(&mut caller, hasher.finalize().to_vec())
 }
 
 pub fn sig_verify(
@@ -107,12 +102,9 @@ pub fn sig_verify(
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
     };
 
-    let message =
-        &memory[offset_message as usize..offset_message as usize + length_message as usize];
-    let signature =
-        &memory[offset_signature as usize..offset_signature as usize + length_signature as usize];
-    let public_key = &memory
-        [offset_public_key as usize..offset_public_key as usize + length_public_key as usize];
+    let message = memory_slice!(memory, offset_message, length_message, 0);
+    let signature = memory_slice!(memory, offset_signature, length_signature, 0);
+    let public_key = memory_slice!(memory, offset_public_key, length_public_key, 0);
 
     match ctx.vm.sig_verify(message, signature, public_key) {
         Ok(result) => (0, result as i32),
@@ -13,11 +13,9 @@ pub fn lease(
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let bytes =
-        &memory[offset_recipient as usize..offset_recipient as usize + length_recipient as usize];
+    let bytes = memory_slice!(memory, offset_recipient, length_recipient, 0, 0);
 
     let asset_holder = match crate::env::get_asset_holder(ctx, 0, version, bytes.to_vec()) {
         Ok(bytes) => bytes,
@@ -28,7 +26,7 @@ pub fn lease(
         .vm
         .lease(contract_id.as_slice(), asset_holder.as_slice(), amount)
     {
-        Ok(result) => crate::env::write_memory(ctx, memory, offset_memory, result),
+        Ok(result) => crate::env::write_memory(&mut caller, result),
         Err(error) => {
             error!("{}", error);
             (error.as_i32(), 0, 0)
@@ -47,8 +45,7 @@ pub fn cancel_lease(
     };
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let lease_id =
-        &memory[offset_lease_id as usize..offset_lease_id as usize + length_lease_id as usize];
+    let lease_id = memory_slice!(memory, offset_lease_id, length_lease_id);
 
     match ctx.vm.cancel_lease(contract_id.as_slice(), lease_id) {
         Ok(_) => 0,
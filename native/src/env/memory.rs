@@ -14,8 +14,8 @@ pub fn binary_equals(
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
     };
 
-    let left = &memory[offset_left as usize..offset_left as usize + length_left as usize];
-    let right = &memory[offset_right as usize..offset_right as usize + length_right as usize];
+    let left = memory_slice!(memory, offset_left, length_left, 0);
+    let right = memory_slice!(memory, offset_right, length_right, 0);
 
     (0, (left == right) as i32)
 }
@@ -32,16 +32,12 @@ pub fn string_equals(
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
     };
 
-    let left = match str::from_utf8(
-        &memory[offset_left as usize..offset_left as usize + length_left as usize],
-    ) {
+    let left = match str::from_utf8(memory_slice!(memory, offset_left, length_left, 0)) {
         Ok(string) => string,
         Err(_) => return (RuntimeError::Utf8Error.as_i32(), 0),
     };
 
-    let right = match str::from_utf8(
-        &memory[offset_right as usize..offset_right as usize + length_right as usize],
-    ) {
+    let right = match str::from_utf8(memory_slice!(memory, offset_right, length_right, 0)) {
         Ok(string) => string,
         Err(_) => return (RuntimeError::Utf8Error.as_i32(), 0),
     };
@@ -56,20 +52,19 @@ pub fn join(
     length_right: u32,
     mut caller: Caller<Runtime>,
 ) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
+    let (memory, _) = match caller.data().memory() {
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
-    let left = &memory[offset_left as usize..offset_left as usize + length_left as usize];
-    let right = &memory[offset_right as usize..offset_right as usize + length_right as usize];
+    let left = memory_slice!(memory, offset_left, length_left, 0, 0);
+    let right = memory_slice!(memory, offset_right, length_right, 0, 0);
 
     let mut result = vec![];
     result.extend_from_slice(left);
     result.extend_from_slice(right);
 
-    crate::env::write_memory(ctx, memory, offset_memory, result)
+    crate::env::write_memory(&mut caller, result)
 }
 
 pub fn contains(
@@ -84,25 +79,37 @@ pub fn contains(
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
     };
 
-    let bytes = &memory[offset_bytes as usize..offset_bytes as usize + length_bytes as usize];
-    let subbytes =
-        &memory[offset_subbytes as usize..offset_subbytes as usize + length_subbytes as usize];
+    let bytes = memory_slice!(memory, offset_bytes, length_bytes, 0);
+    let subbytes = memory_slice!(memory, offset_subbytes, length_subbytes, 0);
 
     let result = bytes.windows(subbytes.len()).any(|item| item == subbytes);
     (0, result as i32)
 }
 
 pub fn drop(offset_bytes: u32, length_bytes: u32, n: i64) -> (i32, u32, u32) {
-    match u32::try_from(n) {
-        Ok(value) => (0, offset_bytes + value, length_bytes - value),
-        Err(_) => (RuntimeError::ConvertingNumericTypes.as_i32(), 0, 0),
+    let value = match u32::try_from(n) {
+        Ok(value) => value,
+        Err(_) => return (RuntimeError::ConvertingNumericTypes.as_i32(), 0, 0),
+    };
+
+    match (
+        offset_bytes.checked_add(value),
+        length_bytes.checked_sub(value),
+    ) {
+        (Some(offset), Some(length)) => (0, offset, length),
+        _ => (RuntimeError::MemoryOutOfBounds.as_i32(), 0, 0),
     }
 }
 
 pub fn drop_right(offset_bytes: u32, length_bytes: u32, n: i64) -> (i32, u32, u32) {
-    match u32::try_from(n) {
-        Ok(value) => (0, offset_bytes, length_bytes - value),
-        Err(_) => (RuntimeError::ConvertingNumericTypes.as_i32(), 0, 0),
+    let value = match u32::try_from(n) {
+        Ok(value) => value,
+        Err(_) => return (RuntimeError::ConvertingNumericTypes.as_i32(), 0, 0),
+    };
+
+    match length_bytes.checked_sub(value) {
+        Some(length) => (0, offset_bytes, length),
+        None => (RuntimeError::MemoryOutOfBounds.as_i32(), 0, 0),
     }
 }
 
@@ -119,19 +126,16 @@ pub fn index_of(
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
     };
 
-    let string = match str::from_utf8(
-        &memory[offset_string as usize..offset_string as usize + length_string as usize],
-    ) {
+    let string = match str::from_utf8(memory_slice!(memory, offset_string, length_string, 0)) {
         Ok(value) => value,
         Err(_) => return (RuntimeError::Utf8Error.as_i32(), 0),
     };
 
-    let substring = match str::from_utf8(
-        &memory[offset_substring as usize..offset_substring as usize + length_substring as usize],
-    ) {
-        Ok(value) => value,
-        Err(_) => return (RuntimeError::Utf8Error.as_i32(), 0),
-    };
+    let substring =
+        match str::from_utf8(memory_slice!(memory, offset_substring, length_substring, 0)) {
+            Ok(value) => value,
+            Err(_) => return (RuntimeError::Utf8Error.as_i32(), 0),
+        };
 
     let result = if is_last {
         string.rfind(substring)
@@ -153,8 +157,16 @@ pub fn take(offset_bytes: u32, _length_bytes: u32, n: i64) -> (i32, u32, u32) {
 }
 
 pub fn take_right(offset_bytes: u32, length_bytes: u32, n: i64) -> (i32, u32, u32) {
-    match u32::try_from(n) {
-        Ok(value) => (0, offset_bytes + (length_bytes - value), value),
-        Err(_) => (RuntimeError::ConvertingNumericTypes.as_i32(), 0, 0),
+    let value = match u32::try_from(n) {
+        Ok(value) => value,
+        Err(_) => return (RuntimeError::ConvertingNumericTypes.as_i32(), 0, 0),
+    };
+
+    match length_bytes
+        .checked_sub(value)
+        .and_then(|rest| offset_bytes.checked_add(rest))
+    {
+        Some(offset) => (0, offset, value),
+        None => (RuntimeError::MemoryOutOfBounds.as_i32(), 0, 0),
     }
 }
@@ -1,7 +1,7 @@
 use crate::{
     error::{ExecutableError, RuntimeError},
     node::Node,
-    runtime::{data_entry::DataEntry, Runtime},
+    runtime::{data_entry::DataEntry, utils, Runtime},
 };
 use wasmi::Caller;
 
@@ -18,12 +18,12 @@ pub fn contains_key(
     };
 
     let address = if length_address != 0 {
-        memory[offset_address as usize..offset_address as usize + length_address as usize].to_vec()
+        memory_slice!(memory, offset_address, length_address, 0).to_vec()
     } else {
         ctx.vm.top_frame().contract_id()
     };
 
-    let key = &memory[offset_key as usize..offset_key as usize + length_key as usize];
+    let key = memory_slice!(memory, offset_key, length_key, 0);
 
     match ctx.vm.contains_key(address.as_slice(), key) {
         Ok(result) => (0, result as i32),
@@ -44,12 +44,12 @@ pub fn get_storage_int(
     };
 
     let address = if length_address != 0 {
-        memory[offset_address as usize..offset_address as usize + length_address as usize].to_vec()
+        memory_slice!(memory, offset_address, length_address, 0).to_vec()
     } else {
         ctx.vm.top_frame().contract_id()
     };
 
-    let key = &memory[offset_key as usize..offset_key as usize + length_key as usize];
+    let key = memory_slice!(memory, offset_key, length_key, 0);
 
     match ctx.vm.get_storage(address.as_slice(), key) {
         Ok(bytes) => match DataEntry::deserialize(bytes.as_slice()) {
@@ -73,12 +73,12 @@ pub fn get_storage_bool(
     };
 
     let address = if length_address != 0 {
-        memory[offset_address as usize..offset_address as usize + length_address as usize].to_vec()
+        memory_slice!(memory, offset_address, length_address, 0).to_vec()
     } else {
         ctx.vm.top_frame().contract_id()
     };
 
-    let key = &memory[offset_key as usize..offset_key as usize + length_key as usize];
+    let key = memory_slice!(memory, offset_key, length_key, 0);
 
     match ctx.vm.get_storage(address.as_slice(), key) {
         Ok(bytes) => match DataEntry::deserialize(bytes.as_slice()) {
@@ -100,15 +100,14 @@ pub fn get_storage_binary(
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
     let address = if length_address != 0 {
-        memory[offset_address as usize..offset_address as usize + length_address as usize].to_vec()
+        memory_slice!(memory, offset_address, length_address, 0, 0).to_vec()
     } else {
         ctx.vm.top_frame().contract_id()
     };
 
-    let key = &memory[offset_key as usize..offset_key as usize + length_key as usize];
+    let key = memory_slice!(memory, offset_key, length_key, 0, 0);
 
     match ctx.vm.get_storage(address.as_slice(), key) {
         Ok(bytes) => {
@@ -116,7 +115,7 @@ pub fn get_storage_binary(
                 Ok(DataEntry::Binary(bytes)) => bytes,
                 _ => return (ExecutableError::FailedDeserialize.as_i32(), 0, 0),
             };
-            crate::env::write_memory(ctx, memory, offset_memory, result)
+            crate::env::write_memory(&mut caller, result)
         }
         Err(error) => (error.as_i32(), 0, 0),
     }
@@ -133,15 +132,14 @@ pub fn get_storage_string(
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
     let address = if length_address != 0 {
-        memory[offset_address as usize..offset_address as usize + length_address as usize].to_vec()
+        memory_slice!(memory, offset_address, length_address, 0, 0).to_vec()
     } else {
         ctx.vm.top_frame().contract_id()
     };
 
-    let key = &memory[offset_key as usize..offset_key as usize + length_key as usize];
+    let key = memory_slice!(memory, offset_key, length_key, 0, 0);
 
     match ctx.vm.get_storage(address.as_slice(), key) {
         Ok(bytes) => {
@@ -149,12 +147,159 @@ pub fn get_storage_string(
                 Ok(DataEntry::String(bytes)) => bytes,
                 _ => return (ExecutableError::FailedDeserialize.as_i32(), 0, 0),
             };
-            crate::env::write_memory(ctx, memory, offset_memory, result)
+            crate::env::write_memory(&mut caller, result)
         }
         Err(error) => (error.as_i32(), 0, 0),
     }
 }
 
+pub fn get_storage_uint(
+    offset_address: u32,
+    length_address: u32,
+    offset_key: u32,
+    length_key: u32,
+    mut caller: Caller<Runtime>,
+) -> (i32, i64) {
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut caller),
+        None => return (RuntimeError::MemoryNotFound.as_i32(), 0),
+    };
+
+    let address = if length_address != 0 {
+        memory_slice!(memory, offset_address, length_address, 0).to_vec()
+    } else {
+        ctx.vm.top_frame().contract_id()
+    };
+
+    let key = memory_slice!(memory, offset_key, length_key, 0);
+
+    match ctx.vm.get_storage(address.as_slice(), key) {
+        Ok(bytes) => match DataEntry::deserialize(bytes.as_slice()) {
+            Ok(DataEntry::UnsignedInteger(integer)) => (0, integer as i64),
+            _ => (ExecutableError::FailedDeserialize.as_i32(), 0),
+        },
+        Err(error) => (error.as_i32(), 0),
+    }
+}
+
+/// Reading the value of the type given by the tag,
+/// written to memory as in `DataEntry::into_tagged`.
+pub fn get_storage_tagged(
+    tag: u8,
+    offset_address: u32,
+    length_address: u32,
+    offset_key: u32,
+    length_key: u32,
+    mut caller: Caller<Runtime>,
+) -> (i32, u32, u32) {
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut caller),
+        None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
+    };
+
+    let address = if length_address != 0 {
+        memory_slice!(memory, offset_address, length_address, 0, 0).to_vec()
+    } else {
+        ctx.vm.top_frame().contract_id()
+    };
+
+    let key = memory_slice!(memory, offset_key, length_key, 0, 0);
+
+    match ctx.vm.get_storage(address.as_slice(), key) {
+        Ok(bytes) => {
+            let result = match DataEntry::deserialize(bytes.as_slice()) {
+                Ok(entry) => match entry.into_tagged() {
+                    (value_tag, value) if value_tag == tag => value,
+                    _ => return (ExecutableError::FailedDeserialize.as_i32(), 0, 0),
+                },
+                Err(error) => return (error.as_i32(), 0, 0),
+            };
+            crate::env::write_memory(&mut caller, result)
+        }
+        Err(error) => (error.as_i32(), 0, 0),
+    }
+}
+
+/// Reading the value of any type.
+/// Returns the type tag of the value, or `-1` if the key is not found.
+pub fn get_storage(
+    offset_address: u32,
+    length_address: u32,
+    offset_key: u32,
+    length_key: u32,
+    mut caller: Caller<Runtime>,
+) -> (i32, i32, u32, u32) {
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut caller),
+        None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0, 0),
+    };
+
+    let address = if length_address != 0 {
+        memory_slice!(memory, offset_address, length_address, 0, 0, 0).to_vec()
+    } else {
+        ctx.vm.top_frame().contract_id()
+    };
+
+    let key = memory_slice!(memory, offset_key, length_key, 0, 0, 0);
+
+    let bytes = match ctx.vm.get_storage(address.as_slice(), key) {
+        Ok(bytes) if bytes.is_empty() => return (0, -1, 0, 0),
+        Ok(bytes) => bytes,
+        Err(error) => return (error.as_i32(), 0, 0, 0),
+    };
+
+    let (tag, value) = match DataEntry::deserialize(bytes.as_slice()) {
+        Ok(entry) => entry.into_tagged(),
+        Err(error) => return (error.as_i32(), 0, 0, 0),
+    };
+
+    match crate::env::write_memory(&mut caller, value) {
+        (0, offset, length) => (0, tag as i32, offset, length),
+        (error, _, _) => (error, 0, 0, 0),
+    }
+}
+
+/// Getting the keys starting with the prefix after the cursor key, at most `limit`.
+/// The keys are written to memory as a list prefixed with the `u16` count,
+/// each key prefixed with the `u16` length. Fuel is charged for each requested key
+/// before the call, so the cost does not depend on the state.
+#[allow(clippy::too_many_arguments)]
+pub fn storage_keys(
+    offset_address: u32,
+    length_address: u32,
+    offset_prefix: u32,
+    length_prefix: u32,
+    offset_cursor: u32,
+    length_cursor: u32,
+    limit: u32,
+    mut caller: Caller<Runtime>,
+) -> (i32, u32, u32) {
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut caller),
+        None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
+    };
+
+    let address = if length_address != 0 {
+        memory_slice!(memory, offset_address, length_address, 0, 0).to_vec()
+    } else {
+        ctx.vm.top_frame().contract_id()
+    };
+
+    let prefix = memory_slice!(memory, offset_prefix, length_prefix, 0, 0);
+    let cursor = memory_slice!(memory, offset_cursor, length_cursor, 0, 0);
+    let limit = limit.min(crate::env::MAX_ENTRIES);
+
+    let keys = match ctx
+        .vm
+        .storage_keys(address.as_slice(), prefix, cursor, limit)
+    {
+        Ok(keys) => keys,
+        Err(error) => return (error.as_i32(), 0, 0),
+    };
+
+    crate::env::write_memory(&mut caller, utils::serialize_list(&keys))
+}
+
 pub fn set_storage_int(
     offset_key: u32,
     length_key: u32,
@@ -167,7 +312,7 @@ pub fn set_storage_int(
     };
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let key = &memory[offset_key as usize..offset_key as usize + length_key as usize];
+    let key = memory_slice!(memory, offset_key, length_key);
     let data_entry = DataEntry::Integer(value).serialize(Some(key));
 
     match ctx
@@ -191,7 +336,7 @@ pub fn set_storage_bool(
     };
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let key = &memory[offset_key as usize..offset_key as usize + length_key as usize];
+    let key = memory_slice!(memory, offset_key, length_key);
     let data_entry = DataEntry::Boolean(value).serialize(Some(key));
 
     match ctx
@@ -216,8 +361,8 @@ pub fn set_storage_binary(
     };
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let key = &memory[offset_key as usize..offset_key as usize + length_key as usize];
-    let value = &memory[offset_value as usize..offset_value as usize + length_value as usize];
+    let key = memory_slice!(memory, offset_key, length_key);
+    let value = memory_slice!(memory, offset_value, length_value);
     let data_entry = DataEntry::Binary(value.to_vec()).serialize(Some(key));
 
     match ctx
@@ -242,8 +387,8 @@ pub fn set_storage_string(
     };
 
     let contract_id = ctx.vm.top_frame().contract_id();
-    let key = &memory[offset_key as usize..offset_key as usize + length_key as usize];
-    let value = &memory[offset_value as usize..offset_value as usize + length_value as usize];
+    let key = memory_slice!(memory, offset_key, length_key);
+    let value = memory_slice!(memory, offset_value, length_value);
     let data_entry = DataEntry::String(value.to_vec()).serialize(Some(key));
 
     match ctx
@@ -254,3 +399,93 @@ pub fn set_storage_string(
         Err(error) => error.as_i32(),
     }
 }
+
+pub fn set_storage_uint(
+    offset_key: u32,
+    length_key: u32,
+    value: i64,
+    mut caller: Caller<Runtime>,
+) -> i32 {
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut caller),
+        None => return RuntimeError::MemoryNotFound.as_i32(),
+    };
+
+    let contract_id = ctx.vm.top_frame().contract_id();
+    let key = memory_slice!(memory, offset_key, length_key);
+    let data_entry = DataEntry::UnsignedInteger(value as u64).serialize(Some(key));
+
+    match ctx
+        .vm
+        .set_storage(contract_id.as_slice(), data_entry.as_slice())
+    {
+        Ok(_) => 0,
+        Err(error) => error.as_i32(),
+    }
+}
+
+/// Writing the value of the type given by the tag,
+/// written to memory as in `DataEntry::into_tagged`.
+pub fn set_storage_tagged(
+    tag: u8,
+    offset_key: u32,
+    length_key: u32,
+    offset_value: u32,
+    length_value: u32,
+    mut caller: Caller<Runtime>,
+) -> i32 {
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut caller),
+        None => return RuntimeError::MemoryNotFound.as_i32(),
+    };
+
+    let contract_id = ctx.vm.top_frame().contract_id();
+    let key = memory_slice!(memory, offset_key, length_key);
+    let value = memory_slice!(memory, offset_value, length_value);
+    let data_entry = match DataEntry::from_tagged(tag, value) {
+        Ok(value) => value.serialize(Some(key)),
+        Err(error) => return error.as_i32(),
+    };
+
+    match ctx
+        .vm
+        .set_storage(contract_id.as_slice(), data_entry.as_slice())
+    {
+        Ok(_) => 0,
+        Err(error) => error.as_i32(),
+    }
+}
+
+pub fn set_storage_batch(
+    offset_values: u32,
+    length_values: u32,
+    mut caller: Caller<Runtime>,
+) -> i32 {
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut caller),
+        None => return RuntimeError::MemoryNotFound.as_i32(),
+    };
+
+    let contract_id = ctx.vm.top_frame().contract_id();
+    let values = memory_slice!(memory, offset_values, length_values);
+
+    match ctx.vm.set_storage_batch(contract_id.as_slice(), values) {
+        Ok(_) => 0,
+        Err(error) => error.as_i32(),
+    }
+}
+
+pub fn delete_storage(offset_key: u32, length_key: u32, mut caller: Caller<Runtime>) -> i32 {
+    let (memory, ctx) = match caller.data().memory() {
+        Some(memory) => memory.data_and_store_mut(&mut caller),
+        None => return RuntimeError::MemoryNotFound.as_i32(),
+    };
+
+    let contract_id = ctx.vm.top_frame().contract_id();
+    let key = memory_slice!(memory, offset_key, length_key);
+
+    match ctx.vm.delete_storage(contract_id.as_slice(), key) {
+        Ok(_) => 0,
+        Err(error) => error.as_i32(),
+    }
+}
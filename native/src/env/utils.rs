@@ -8,21 +8,18 @@ pub fn base58(
     length_bytes: u32,
     mut caller: Caller<Runtime>,
 ) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
+    let (memory, _) = match caller.data().memory() {
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
-    let value = match str::from_utf8(
-        &memory[offset_bytes as usize..offset_bytes as usize + length_bytes as usize],
-    ) {
+    let value = match str::from_utf8(memory_slice!(memory, offset_bytes, length_bytes, 0, 0)) {
         Ok(string) => string,
         Err(_) => return (RuntimeError::Utf8Error.as_i32(), 0, 0),
     };
 
     match value.from_base58() {
-        Ok(result) => crate::env::write_memory(ctx, memory, offset_memory, result),
+        Ok(result) => crate::env::write_memory(&mut caller, result),
         Err(_) => (RuntimeError::Base58Error.as_i32(), 0, 0),
     }
 }
@@ -32,16 +29,15 @@ pub fn to_base58_string(
     length_bytes: u32,
     mut caller: Caller<Runtime>,
 ) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
+    let (memory, _) = match caller.data().memory() {
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
-    let value = &memory[offset_bytes as usize..offset_bytes as usize + length_bytes as usize];
+    let value = memory_slice!(memory, offset_bytes, length_bytes, 0, 0);
 
     let result = value.to_base58().as_bytes().to_vec();
-    crate::env::write_memory(ctx, memory, offset_memory, result)
+    crate::env::write_memory(&mut caller, result)
 }
 
 pub fn to_le_bytes(
@@ -49,29 +45,21 @@ pub fn to_le_bytes(
     length_bytes: u32,
     mut caller: Caller<Runtime>,
 ) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
+    let (memory, _) = match caller.data().memory() {
         Some(memory) => memory.data_and_store_mut(&mut caller),
         None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
     };
-    let offset_memory = ctx.heap_base() as usize;
 
-    let mut result =
-        memory[offset_bytes as usize..offset_bytes as usize + length_bytes as usize].to_vec();
+    let mut result = memory_slice!(memory, offset_bytes, length_bytes, 0, 0).to_vec();
     result.reverse();
 
-    crate::env::write_memory(ctx, memory, offset_memory, result)
+    crate::env::write_memory(&mut caller, result)
 }
 
 pub fn caller(mut caller: Caller<Runtime>) -> (i32, u32, u32) {
-    let (memory, ctx) = match caller.data().memory() {
-        Some(memory) => memory.data_and_store_mut(&mut caller),
-        None => return (RuntimeError::MemoryNotFound.as_i32(), 0, 0),
-    };
-    let offset_memory = ctx.heap_base() as usize;
-
-    let result = ctx.vm.get_caller_current_frame();
+    let result = caller.data().vm.get_caller_current_frame();
 
-    crate::env::write_memory(ctx, memory, offset_memory, result)
+    crate::env::write_memory(&mut caller, result)
 }
 
 pub fn require(offset_message: u32, length_message: u32, mut caller: Caller<Runtime>) -> i32 {
@@ -80,8 +68,7 @@ pub fn require(offset_message: u32, length_message: u32, mut caller: Caller<Runt
         None => return RuntimeError::MemoryNotFound.as_i32(),
     };
 
-    let message =
-        &memory[offset_message as usize..offset_message as usize + length_message as usize];
+    let message = memory_slice!(memory, offset_message, length_message);
 
     if str::from_utf8(message).is_err() {
         return RuntimeError::Utf8Error.as_i32();
@@ -1,3 +1,4 @@
+#[cfg(feature = "jvm")]
 use jni::sys::jint;
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -36,6 +37,7 @@ macro_rules! error {
                 }
             }
 
+            #[cfg(feature = "jvm")]
             pub fn as_jint(&self) -> jint {
                 match self {
                     $($name::$variant $( ($value) )? => $code,)+
@@ -65,6 +67,7 @@ impl core::fmt::Display for Error {
 
 impl Error {
     /// Converting an error to a numeric code of `jint` type.
+    #[cfg(feature = "jvm")]
     pub fn as_jint(&self) -> jint {
         match self {
             Error::Executable(error) => error.as_jint(),
@@ -132,6 +135,30 @@ error! {
         #[error("Fuel metering is disabled")]
         #[code(114)]
         FuelMeteringDisabled(_message: String),
+        #[error("Panic during execution")]
+        #[code(115)]
+        Panic(_message: String),
+        #[error("Invalid ABI of the contract")]
+        #[code(116)]
+        InvalidAbi(_message: String),
+        #[error("Imports of the contract are not provided by the host")]
+        #[code(117)]
+        InvalidImports(_message: String),
+        #[error("Bytecode contains non-deterministic constructs or exceeds the limits")]
+        #[code(118)]
+        LintFailed(_message: String),
+        #[error("Bytecode of the contract exceeds the size limit")]
+        #[code(119)]
+        BytecodeSizeLimit(_message: String),
+        #[error("Function arguments exceed the size limit")]
+        #[code(120)]
+        ParamsSizeLimit(_message: String),
+        #[error("Invalid configuration of the virtual machine")]
+        #[code(121)]
+        InvalidConfig(_message: String),
+        #[error("Execution exceeded the time limit")]
+        #[code(122)]
+        Timeout,
     }
 }
 
@@ -213,6 +240,18 @@ error! {
         #[error("Value parsing error")]
         #[code(308)]
         ParseError,
+        #[error("The node rejected the operation")]
+        #[code(309)]
+        NodeRejected(_message: String),
+        #[error("Out of bounds access to linear memory")]
+        #[code(310)]
+        MemoryOutOfBounds,
+        #[error("Failed to allocate memory in the contract")]
+        #[code(311)]
+        AllocationFailed(_message: String),
+        #[error("State changes are not allowed in read-only mode")]
+        #[code(312)]
+        ReadOnlyViolation,
     }
 }
 
@@ -224,12 +263,15 @@ mod tests {
     fn test_error() {
         let message: String = String::from("Error message");
 
-        assert_eq!(
-            ExecutableError::InvalidBytecode(message.clone()).as_jint(),
-            100
-        );
-        assert_eq!(JvmError::JvmNotFound.as_jint(), 200);
-        assert_eq!(RuntimeError::Exception(message.clone()).as_jint(), 300);
+        #[cfg(feature = "jvm")]
+        {
+            assert_eq!(
+                ExecutableError::InvalidBytecode(message.clone()).as_jint(),
+                100
+            );
+            assert_eq!(JvmError::JvmNotFound.as_jint(), 200);
+            assert_eq!(RuntimeError::Exception(message.clone()).as_jint(), 300);
+        }
 
         assert_eq!(
             ExecutableError::InvalidBytecode(message.clone()).as_i32(),
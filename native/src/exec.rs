@@ -1,13 +1,21 @@
+pub mod abi;
+pub mod imports;
+pub mod lint;
+pub mod module_cache;
+
 use crate::{
     error::{Error, ExecutableError, Result, RuntimeError},
+    exec::abi::Abi,
     modules::Module as M,
-    runtime::{data_entry::DataEntry, Runtime},
-    vm::Vm,
+    runtime::{self, data_entry::DataEntry, Runtime},
+    vm::{
+        config::{StackConfig, VmConfig},
+        Vm,
+    },
 };
-use std::{fmt, str::FromStr};
+use std::{fmt, str::FromStr, sync::Arc};
 use wasmi::{
-    core::ValueType, Config, Engine, Func, FuncType, Memory, MemoryType, Module, StackLimits,
-    Store, Value,
+    core::ValueType, Config, Engine, Func, FuncType, Memory, MemoryType, Module, Store, Value,
 };
 
 /// Enumeration of possible executable functions of a WASM contract.
@@ -38,40 +46,86 @@ impl fmt::Display for LoadableFunction {
 
 #[derive(Debug)]
 pub struct Executable {
-    module: Option<Module>,
+    module: Option<Arc<Module>>,
+    /// Description of the exported functions, if the contract has one.
+    abi: Option<Arc<Abi>>,
     /// Initial memory size of a contract's sandbox.
     initial: u32,
     /// The maximum memory size of a contract's sandbox.
     maximum: u32,
+    /// Limits of the Wasm stack the module is compiled with.
+    stack: StackConfig,
     /// Fuel limit for contract execution.
     fuel_limit: u64,
 }
 
 impl Executable {
     /// Initializing the WASM contract executable.
-    pub fn new(initial: u32, maximum: u32, fuel_limit: u64) -> Self {
+    pub fn new(config: &VmConfig, fuel_limit: u64) -> Self {
         Self {
             module: None,
-            initial,
-            maximum,
+            abi: None,
+            initial: config.memory.0,
+            maximum: config.memory.1,
+            stack: config.stack,
             fuel_limit,
         }
     }
 
     /// Load bytecode for execution.
+    /// Compiled modules and their ABI are taken from the process-wide [`module_cache`].
     pub fn load_bytecode(&mut self, bytecode: &[u8]) -> Result<()> {
-        self.module = Some(Self::create_module(bytecode)?);
+        let compiled = module_cache::load(bytecode, &self.stack)?;
+        self.module = Some(compiled.module);
+        self.abi = compiled.abi;
         Ok(())
     }
 
-    /// Validate bytecode contract.
-    pub fn validate_bytecode(bytecode: &[u8]) -> Result<Module> {
-        Self::create_module(bytecode)
+    pub fn abi(&self) -> Option<&Abi> {
+        self.abi.as_deref()
+    }
+
+    /// Validate bytecode contract against the limits of the config, including the ABI
+    /// if the contract has one. Imports are checked against the host functions
+    /// of all module versions, the bytecode is checked by the linter with the default limits.
+    pub fn validate_bytecode(bytecode: &[u8], config: &VmConfig) -> Result<Module> {
+        config.validate()?;
+
+        if bytecode.len() > config.max_bytecode_size {
+            return Err(Error::Executable(ExecutableError::BytecodeSizeLimit(
+                format!(
+                    "Bytecode size {} exceeds the limit {}",
+                    bytecode.len(),
+                    config.max_bytecode_size
+                ),
+            )));
+        }
+
+        let (module, _) = Self::compile(&Self::engine(&config.stack)?, bytecode)?;
+
+        let report = lint::lint(bytecode, &config.lint)?;
+        if report.has_errors() {
+            return Err(Error::Executable(ExecutableError::LintFailed(
+                report.errors(),
+            )));
+        }
+
+        let invalid = imports::check(&module, &crate::signatures());
+        if !invalid.is_empty() {
+            let message = invalid
+                .iter()
+                .map(|item| item.to_string())
+                .collect::<Vec<_>>()
+                .join("; ");
+            return Err(Error::Executable(ExecutableError::InvalidImports(message)));
+        }
+
+        Ok(module)
     }
 
-    /// Initializing `Engine` and `Module`.
-    fn create_module(bytecode: &[u8]) -> Result<Module> {
-        let stack_limits = StackLimits::default();
+    /// Initializing `Engine` with the limits of the Wasm stack.
+    pub(crate) fn engine(stack: &StackConfig) -> Result<Engine> {
+        let stack_limits = stack.stack_limits()?;
 
         let mut config = Config::default();
         config
@@ -87,10 +141,12 @@ impl Executable {
             .floats(false)
             .consume_fuel(true);
 
-        let engine = Engine::new(&config);
-        let module = Module::new(&engine, &mut &bytecode[..]).map_err(|error| {
-            Error::Executable(ExecutableError::InvalidBytecode(format!("{:?}", error)))
-        })?;
+        Ok(Engine::new(&config))
+    }
+
+    /// Initializing `Module` and reading the ABI, if the contract has one.
+    pub(crate) fn compile(engine: &Engine, bytecode: &[u8]) -> Result<(Module, Option<Abi>)> {
+        let module = Self::compile_module(engine, bytecode)?;
 
         if module
             .exports()
@@ -101,7 +157,19 @@ impl Executable {
             return Err(Error::Executable(ExecutableError::ConstructorNotFound));
         }
 
-        Ok(module)
+        let abi = Abi::from_bytecode(bytecode)?;
+        if let Some(abi) = &abi {
+            abi.validate(&module)?;
+        }
+
+        Ok((module, abi))
+    }
+
+    /// Initializing `Module` only, for the bytecode that already passed [`Executable::compile`].
+    pub(crate) fn compile_module(engine: &Engine, bytecode: &[u8]) -> Result<Module> {
+        Module::new(engine, &mut &bytecode[..]).map_err(|error| {
+            Error::Executable(ExecutableError::InvalidBytecode(format!("{:?}", error)))
+        })
     }
 
     /// Execution of the WASM contract function.
@@ -129,23 +197,43 @@ impl Executable {
             Some(memory) => memory,
             None => return Err(Error::Runtime(RuntimeError::MemoryNotFound)),
         };
-        let mut offset_memory = store.data().heap_base() as usize;
+        // Serialized params are not smaller than the binary and string values written to memory
+        let mut offset_memory = match params.is_empty() {
+            true => store.data().heap_base() as usize,
+            false => runtime::allocate(&mut store, params.len())?,
+        };
         let array_memory = memory.data_mut(&mut store);
 
-        let func_args: Vec<String> =
-            DataEntry::deserialize_params(params, array_memory, &mut offset_memory)?;
-
-        store.data_mut().set_heap_base(offset_memory as i32);
+        let func_abi = self
+            .abi
+            .as_ref()
+            .and_then(|abi| abi.function(&func_name.to_string()));
+        let func_args =
+            DataEntry::deserialize_params(params, array_memory, &mut offset_memory, func_abi)?;
 
         let func_type = func.ty(&store);
-        let func_args = Self::type_check_arguments(&func_type, func_args.as_slice())?;
+        let func_args = Self::type_check_arguments(&func_type, func_args)?;
 
         let mut results = Self::prepare_results_buffer(&func_type);
 
-        func.call(&mut store, &func_args, &mut results)
-            .map_err(|error| {
-                Error::Executable(ExecutableError::FailedExec(format!("{:?}", error)))
-            })?;
+        let result = func.call(&mut store, &func_args, &mut results);
+
+        // Fuel is shared by all frames of the call stack,
+        // so what remains is returned to the VM even if the call failed
+        let fuel_consumed = store.fuel_consumed().unwrap_or(self.fuel_limit);
+        store
+            .data_mut()
+            .vm
+            .set_fuel(self.fuel_limit.saturating_sub(fuel_consumed));
+
+        result.map_err(|error| {
+            Error::Executable(ExecutableError::FailedExec(format!("{:?}", error)))
+        })?;
+
+        // Data is only returned by a successful call
+        if let (Some(func_abi), [Value::I32(0)]) = (func_abi, results.as_slice()) {
+            func_abi.check_results(store.data().vm.top_frame().return_data())?;
+        }
 
         Ok(results)
     }
@@ -217,6 +305,11 @@ impl Executable {
 
         store.data_mut().set_heap_base(heap_base);
 
+        // Contracts exporting an allocator receive data from the host in memory it allocated
+        if let Ok(allocator) = instance.get_typed_func::<u32, u32>(&store, "allocate") {
+            store.data_mut().set_allocator(allocator);
+        }
+
         let func = instance
             .get_export(&store, func_name)
             .and_then(|ext| ext.into_func())
@@ -225,45 +318,48 @@ impl Executable {
         Ok((func, store))
     }
 
-    /// Type checks the given function arguments and returns them decoded into [`Value`]s.
+    /// Type checks the given function arguments against the function parameters.
+    ///
+    /// An `i32` argument is widened for an `i64` parameter, an `i64` argument
+    /// is accepted by an `i32` parameter if the value fits.
     ///
     /// # Errors
     ///
     /// - If the number of given arguments is not equal to the number of function parameters.
-    /// - If an argument cannot be properly parsed to its expected parameter type.
-    fn type_check_arguments(func_type: &FuncType, func_args: &[String]) -> Result<Vec<Value>> {
+    /// - If an argument cannot be converted to the type of its parameter.
+    fn type_check_arguments(func_type: &FuncType, func_args: Vec<Value>) -> Result<Vec<Value>> {
         if func_type.params().len() != func_args.len() {
             return Err(Error::Executable(ExecutableError::InvalidNumArgs));
         }
 
-        let func_args = func_type
+        func_type
             .params()
             .iter()
             .zip(func_args)
             .enumerate()
-            .map(|(_, (param_type, arg))| {
-                macro_rules! make_err {
-                    () => {
-                        |error| {
-                            Error::Executable(ExecutableError::FailedParseFuncArgs(format!(
-                                "{:?}",
-                                error
-                            )))
-                        }
-                    };
-                }
-
-                match param_type {
-                    ValueType::I32 => arg.parse::<i32>().map(Value::from).map_err(make_err!()),
-                    ValueType::I64 => arg.parse::<i64>().map(Value::from).map_err(make_err!()),
-                    _ => Err(Error::Executable(ExecutableError::FailedParseFuncArgs(
-                        "Not known or inappropriate argument type".to_string(),
+            .map(|(index, (param_type, arg))| match (param_type, arg) {
+                (ValueType::I32, Value::I32(value)) => Ok(Value::I32(value)),
+                (ValueType::I64, Value::I64(value)) => Ok(Value::I64(value)),
+                (ValueType::I64, Value::I32(value)) => Ok(Value::I64(value.into())),
+                (ValueType::I32, Value::I64(value)) => match i32::try_from(value) {
+                    Ok(value) => Ok(Value::I32(value)),
+                    Err(_) => Err(Error::Executable(ExecutableError::FailedParseFuncArgs(
+                        format!(
+                            "Parameter {}: expected I32, found I64 value {} out of range",
+                            index, value
+                        ),
                     ))),
-                }
+                },
+                (param_type, arg) => Err(Error::Executable(ExecutableError::FailedParseFuncArgs(
+                    format!(
+                        "Parameter {}: expected {:?}, found {:?}",
+                        index,
+                        param_type,
+                        arg.ty()
+                    ),
+                ))),
             })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(func_args)
+            .collect()
     }
 
     /// Returns a [`Value`] buffer capable of holding the return values.
@@ -280,12 +376,14 @@ impl Executable {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tests::wat2wasm;
+    use crate::{exec::lint::LintLimits, tests::wat2wasm};
 
     #[test]
     fn test_executable_valid_bytecode() {
         let wat = r#"
         (module
+            (import "env" "memory" (memory 1 1))
+
             (type $t0 (func (result i32)))
             (func $_constructor (export "_constructor") (type $t0) (result i32)
                 (i32.add
@@ -293,13 +391,36 @@ mod tests {
                     (i32.const 2)
                 )
             )
+
+            (global $__heap_base (export "__heap_base") i32 (i32.const 0))
         )
         "#;
 
         let bytecode = wat2wasm(wat).expect("WAT code parsing failed");
-        let exec = Executable::validate_bytecode(&bytecode);
+        let exec = Executable::validate_bytecode(&bytecode, &VmConfig::default());
 
         assert!(exec.is_ok());
+
+        // The bytecode is validated against the config passed with the call
+        let config = VmConfig {
+            max_bytecode_size: bytecode.len() - 1,
+            ..Default::default()
+        };
+        let exec = Executable::validate_bytecode(&bytecode, &config);
+        assert!(matches!(
+            exec,
+            Err(Error::Executable(ExecutableError::BytecodeSizeLimit(_)))
+        ));
+
+        let config = VmConfig {
+            memory: (2, 1),
+            ..Default::default()
+        };
+        let exec = Executable::validate_bytecode(&bytecode, &config);
+        assert!(matches!(
+            exec,
+            Err(Error::Executable(ExecutableError::InvalidConfig(_)))
+        ));
     }
 
     #[test]
@@ -317,7 +438,7 @@ mod tests {
         "#;
 
         let bytecode = wat2wasm(wat).expect("WAT code parsing failed");
-        let exec = Executable::validate_bytecode(&bytecode);
+        let exec = Executable::validate_bytecode(&bytecode, &VmConfig::default());
 
         assert!(exec.is_err());
         assert_eq!(
@@ -325,4 +446,105 @@ mod tests {
             Error::Executable(ExecutableError::ConstructorNotFound)
         );
     }
+
+    #[test]
+    fn test_type_check_arguments() {
+        let func_type = FuncType::new([ValueType::I32, ValueType::I64], []);
+
+        let args =
+            Executable::type_check_arguments(&func_type, vec![Value::I64(5), Value::I32(-1)])
+                .expect("Type check failed");
+        assert_eq!(args[0].i32(), Some(5));
+        assert_eq!(args[1].i64(), Some(-1));
+
+        let result =
+            Executable::type_check_arguments(&func_type, vec![Value::I64(i64::MAX), Value::I64(0)]);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Executable(ExecutableError::FailedParseFuncArgs(format!(
+                "Parameter 0: expected I32, found I64 value {} out of range",
+                i64::MAX
+            )))
+        );
+
+        let result = Executable::type_check_arguments(&func_type, vec![Value::I32(0)]);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Executable(ExecutableError::InvalidNumArgs)
+        );
+
+        let func_type = FuncType::new([ValueType::F32], []);
+        let result = Executable::type_check_arguments(&func_type, vec![Value::I32(0)]);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Executable(ExecutableError::FailedParseFuncArgs(
+                "Parameter 0: expected F32, found I32".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_executable_invalid_imports() {
+        let wat = r#"
+        (module
+            (import "env0" "get_storage_int" (func $get_storage_int (param i32 i32 i32 i32) (result i32 i64)))
+            (import "env0" "nonexistent" (func $nonexistent))
+            (import "env1" "get_balance" (func $get_balance (param i32 i32)))
+
+            (func (export "_constructor") (result i32)
+                (i32.const 0)
+            )
+        )
+        "#;
+
+        let bytecode = wat2wasm(wat).expect("WAT code parsing failed");
+        let module = Executable::validate_bytecode(&bytecode, &VmConfig::default());
+
+        let message = match module {
+            Err(Error::Executable(ExecutableError::InvalidImports(message))) => message,
+            _ => panic!("Expected InvalidImports"),
+        };
+
+        let invalid: Vec<&str> = message.split("; ").collect();
+        assert_eq!(invalid.len(), 4);
+        assert_eq!(invalid[0], "env0.nonexistent: unknown host function");
+        assert!(invalid[1].starts_with("env1.get_balance: expected signature"));
+        assert_eq!(invalid[2], "env.memory: memory import is missing");
+        assert_eq!(invalid[3], "__heap_base: global export is missing");
+    }
+
+    #[test]
+    fn test_executable_lint_failed() {
+        let wat = r#"
+        (module
+            (import "env" "memory" (memory 1 1024))
+
+            (func (export "_constructor") (result i32)
+                (i32.const 0)
+            )
+
+            (global $__heap_base (export "__heap_base") i32 (i32.const 0))
+        )
+        "#;
+
+        let bytecode = wat2wasm(wat).expect("WAT code parsing failed");
+        let exec = Executable::validate_bytecode(&bytecode, &VmConfig::default());
+
+        assert_eq!(
+            exec.unwrap_err(),
+            Error::Executable(ExecutableError::LintFailed(
+                "Memory maximum is 1024 pages, the limit is 16".to_string()
+            ))
+        );
+
+        // The limits are taken from the config passed with the call
+        let config = VmConfig {
+            lint: LintLimits {
+                max_memory_pages: 1024,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(Executable::validate_bytecode(&bytecode, &config).is_ok());
+    }
 }
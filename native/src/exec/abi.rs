@@ -0,0 +1,382 @@
+use crate::{
+    error::{Error, ExecutableError, Result},
+    runtime::{
+        data_entry::{
+            DataEntry, TAG_BINARY, TAG_BOOLEAN, TAG_INTEGER, TAG_INTEGER128, TAG_INTEGER256,
+            TAG_LIST, TAG_MAP, TAG_STRING, TAG_UNSIGNED_INTEGER,
+        },
+        utils,
+    },
+};
+use wasmi::{core::ValueType, ExternType, Module};
+use wasmparser_nostd::{BinaryReaderError, Parser, Payload};
+
+/// Name of the custom section describing the functions of the contract.
+pub const SECTION_NAME: &str = "wevm:abi";
+
+/// Logical parameter of a function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    /// Type tag of the `DataEntry` passed as the argument.
+    pub type_tag: u8,
+}
+
+/// Signature of an exported function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<Param>,
+    /// Type tags of the `DataEntry` values returned with `set_return`,
+    /// serialized in the format of the function arguments.
+    pub results: Vec<u8>,
+}
+
+impl Function {
+    /// Checking the data returned with `set_return` against the result types.
+    pub fn check_results(&self, return_data: &[u8]) -> Result<()> {
+        let values = DataEntry::deserialize_list(return_data).map_err(|_| {
+            invalid_abi(format!(
+                "Function {} returned data that is not a list of values",
+                self.name
+            ))
+        })?;
+        let tags: Vec<u8> = values.iter().map(DataEntry::type_tag).collect();
+
+        if tags != self.results {
+            return Err(invalid_abi(format!(
+                "Results of function {} do not match the ABI: expected {:?}, found {:?}",
+                self.name, self.results, tags
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Description of the exported functions of a contract, read from the `wevm:abi` custom section.
+///
+/// The section holds the `u16` count of functions, each function is given by
+/// its name, the `u16` count of parameters with their names and type tags,
+/// and the `u16` count of result type tags. Names are prefixed with the `u16` length,
+/// type tags are `u8`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Abi {
+    functions: Vec<Function>,
+}
+
+impl Abi {
+    pub fn new(functions: Vec<Function>) -> Self {
+        Self { functions }
+    }
+
+    /// Reading the ABI from the custom section of the bytecode, if there is one.
+    pub fn from_bytecode(bytecode: &[u8]) -> Result<Option<Self>> {
+        match custom_section(bytecode, SECTION_NAME)? {
+            Some(section) => Ok(Some(Self::deserialize(section)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn deserialize(input: &[u8]) -> Result<Self> {
+        let mut offset = 0;
+        let mut functions = vec![];
+
+        let count = get_u16(input, &mut offset)?;
+        for _ in 0..count {
+            let name = get_string(input, &mut offset)?;
+
+            let mut params = vec![];
+            for _ in 0..get_u16(input, &mut offset)? {
+                params.push(Param {
+                    name: get_string(input, &mut offset)?,
+                    type_tag: get_u8(input, &mut offset)?,
+                });
+            }
+
+            let mut results = vec![];
+            for _ in 0..get_u16(input, &mut offset)? {
+                results.push(get_u8(input, &mut offset)?);
+            }
+
+            functions.push(Function {
+                name,
+                params,
+                results,
+            });
+        }
+
+        if offset != input.len() {
+            return Err(invalid_abi("Unexpected bytes at the end of the section"));
+        }
+
+        Ok(Self { functions })
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut result = (self.functions.len() as u16).to_be_bytes().to_vec();
+
+        for function in self.functions.iter() {
+            put_string(&mut result, &function.name);
+
+            result.extend_from_slice(&(function.params.len() as u16).to_be_bytes());
+            for param in function.params.iter() {
+                put_string(&mut result, &param.name);
+                result.push(param.type_tag);
+            }
+
+            result.extend_from_slice(&(function.results.len() as u16).to_be_bytes());
+            result.extend_from_slice(&function.results);
+        }
+
+        result
+    }
+
+    pub fn functions(&self) -> &[Function] {
+        &self.functions
+    }
+
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    /// Checking that each described function is exported only once,
+    /// its parameters are lowered to the parameters of the export
+    /// and the export returns the `i32` result code.
+    pub fn validate(&self, module: &Module) -> Result<()> {
+        for (index, function) in self.functions.iter().enumerate() {
+            if self.functions[..index]
+                .iter()
+                .any(|item| item.name == function.name)
+            {
+                return Err(invalid_abi(format!(
+                    "Function {} is described twice",
+                    function.name
+                )));
+            }
+
+            let func_type = match module.get_export(&function.name) {
+                Some(ExternType::Func(func_type)) => func_type,
+                _ => {
+                    return Err(invalid_abi(format!(
+                        "Function {} is not exported",
+                        function.name
+                    )))
+                }
+            };
+
+            let mut lowered: Vec<ValueType> = vec![];
+            for param in function.params.iter() {
+                lowered.extend(lower(param.type_tag).ok_or_else(|| {
+                    invalid_abi(format!(
+                        "Parameter {} of function {} has unknown type {}",
+                        param.name, function.name, param.type_tag
+                    ))
+                })?);
+            }
+
+            if lowered != func_type.params() {
+                return Err(invalid_abi(format!(
+                    "Parameters of function {} do not match the export: expected {:?}, found {:?}",
+                    function.name,
+                    lowered,
+                    func_type.params()
+                )));
+            }
+
+            if func_type.results() != [ValueType::I32] {
+                return Err(invalid_abi(format!(
+                    "Function {} must return {:?}, found {:?}",
+                    function.name,
+                    [ValueType::I32],
+                    func_type.results()
+                )));
+            }
+
+            if let Some(tag) = function.results.iter().find(|tag| lower(**tag).is_none()) {
+                return Err(invalid_abi(format!(
+                    "Result of function {} has unknown type {}",
+                    function.name, tag
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Types of the function parameters the `DataEntry` of the type is passed as.
+fn lower(type_tag: u8) -> Option<&'static [ValueType]> {
+    match type_tag {
+        TAG_INTEGER | TAG_UNSIGNED_INTEGER => Some(&[ValueType::I64]),
+        TAG_BOOLEAN => Some(&[ValueType::I32]),
+        TAG_BINARY | TAG_STRING | TAG_INTEGER128 | TAG_INTEGER256 | TAG_LIST | TAG_MAP => {
+            Some(&[ValueType::I32, ValueType::I32])
+        }
+        _ => None,
+    }
+}
+
+/// Getting the payload of the custom section with the name.
+fn custom_section<'a>(bytecode: &'a [u8], name: &str) -> Result<Option<&'a [u8]>> {
+    let mut result = None;
+
+    for payload in Parser::new(0).parse_all(bytecode) {
+        if let Payload::CustomSection(reader) = payload.map_err(invalid_bytecode)? {
+            if reader.name() == name {
+                if result.is_some() {
+                    return Err(invalid_abi(format!("Section {} is repeated", name)));
+                }
+                result = Some(reader.data());
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn get_u8(input: &[u8], offset: &mut usize) -> Result<u8> {
+    utils::get_u8(input, offset).map_err(|_| invalid_abi("Unexpected end of the section"))
+}
+
+fn get_u16(input: &[u8], offset: &mut usize) -> Result<u16> {
+    utils::get_u16(input, offset).map_err(|_| invalid_abi("Unexpected end of the section"))
+}
+
+fn get_string(input: &[u8], offset: &mut usize) -> Result<String> {
+    let length = get_u16(input, offset)?;
+    let bytes = utils::get_bytes(input, offset, length as usize)
+        .map_err(|_| invalid_abi("Unexpected end of the section"))?;
+    String::from_utf8(bytes).map_err(|_| invalid_abi("Name is not a valid UTF-8 string"))
+}
+
+fn put_string(output: &mut Vec<u8>, value: &str) {
+    output.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    output.extend_from_slice(value.as_bytes());
+}
+
+fn invalid_abi(message: impl Into<String>) -> Error {
+    Error::Executable(ExecutableError::InvalidAbi(message.into()))
+}
+
+fn invalid_bytecode(error: BinaryReaderError) -> Error {
+    Error::Executable(ExecutableError::InvalidBytecode(format!("{:?}", error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        exec::{module_cache, Executable},
+        runtime::data_entry::TAG_DELETED,
+        tests::wat2wasm,
+        vm::config::{StackConfig, VmConfig},
+    };
+    use std::sync::Arc;
+
+    fn contract(abi: Option<&Abi>) -> Vec<u8> {
+        let section = match abi {
+            Some(abi) => {
+                let bytes: String = abi
+                    .as_bytes()
+                    .iter()
+                    .map(|byte| format!("\\{:02x}", byte))
+                    .collect();
+                format!(r#"(@custom "{}" "{}")"#, SECTION_NAME, bytes)
+            }
+            None => String::new(),
+        };
+
+        wat2wasm(&format!(
+            r#"
+            (module
+                {}
+                (import "env" "memory" (memory 1 1))
+
+                (func (export "_constructor") (result i32)
+                    (i32.const 0)
+                )
+
+                (func (export "transfer") (param i32 i32 i64) (result i32)
+                    (i32.const 0)
+                )
+
+                (func (export "balance") (result i64)
+                    (i64.const 0)
+                )
+
+                (global $__heap_base (export "__heap_base") i32 (i32.const 0))
+            )
+            "#,
+            section
+        ))
+        .expect("WAT code parsing failed")
+    }
+
+    fn transfer(params: Vec<(&str, u8)>) -> Abi {
+        Abi::new(vec![Function {
+            name: "transfer".to_string(),
+            params: params
+                .into_iter()
+                .map(|(name, type_tag)| Param {
+                    name: name.to_string(),
+                    type_tag,
+                })
+                .collect(),
+            results: vec![],
+        }])
+    }
+
+    #[test]
+    fn test_abi() {
+        assert_eq!(Abi::from_bytecode(&contract(None)), Ok(None));
+
+        let abi = transfer(vec![("recipient", TAG_BINARY), ("amount", TAG_INTEGER)]);
+        let bytecode = contract(Some(&abi));
+        assert_eq!(Abi::from_bytecode(&bytecode), Ok(Some(abi.clone())));
+        assert!(Executable::validate_bytecode(&bytecode, &VmConfig::default()).is_ok());
+
+        // The ABI is parsed once and cached together with the module
+        let stack = StackConfig::default();
+        let first = module_cache::load(&bytecode, &stack).expect("Compilation failed");
+        let cached = first.abi.clone().expect("ABI not found");
+        assert_eq!(*cached, abi);
+        drop(first);
+        let second = module_cache::load(&bytecode, &stack).expect("Compilation failed");
+        assert!(second.abi.is_some_and(|abi| Arc::ptr_eq(&abi, &cached)));
+
+        // Binary and string are passed as two parameters
+        let abi = transfer(vec![("recipient", TAG_INTEGER), ("amount", TAG_INTEGER)]);
+        let result = Executable::validate_bytecode(&contract(Some(&abi)), &VmConfig::default());
+        assert!(matches!(
+            result,
+            Err(Error::Executable(ExecutableError::InvalidAbi(_)))
+        ));
+
+        let mut abi = transfer(vec![("recipient", TAG_BINARY), ("amount", TAG_INTEGER)]);
+        abi.functions[0].name = "missing".to_string();
+        let result = Executable::validate_bytecode(&contract(Some(&abi)), &VmConfig::default());
+        assert!(matches!(
+            result,
+            Err(Error::Executable(ExecutableError::InvalidAbi(_)))
+        ));
+
+        let abi = transfer(vec![("recipient", TAG_DELETED)]);
+        let result = Executable::validate_bytecode(&contract(Some(&abi)), &VmConfig::default());
+        assert!(matches!(
+            result,
+            Err(Error::Executable(ExecutableError::InvalidAbi(_)))
+        ));
+
+        // The export must return the result code
+        let mut abi = transfer(vec![]);
+        abi.functions[0].name = "balance".to_string();
+        let result = Executable::validate_bytecode(&contract(Some(&abi)), &VmConfig::default());
+        assert!(matches!(
+            result,
+            Err(Error::Executable(ExecutableError::InvalidAbi(_)))
+        ));
+
+        assert!(Abi::deserialize(&[0, 1, 0]).is_err());
+    }
+}
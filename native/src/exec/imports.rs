@@ -0,0 +1,129 @@
+use crate::modules::Signature;
+use std::fmt;
+use wasmi::{core::ValueType, ExternType, Module};
+
+/// Import or export of the contract that does not match the host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidImport {
+    /// `module.name` of the import, or the name of the export.
+    pub name: String,
+    pub reason: String,
+}
+
+impl fmt::Display for InvalidImport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.reason)
+    }
+}
+
+/// Checking that each import of the module is provided by the host with the same signature,
+/// that `env.memory` is imported and `__heap_base` is exported.
+pub fn check(module: &Module, signatures: &[Signature]) -> Vec<InvalidImport> {
+    let mut result = vec![];
+    let mut memory = false;
+
+    for import in module.imports() {
+        let name = format!("{}.{}", import.module(), import.name());
+
+        let reason = match (import.module(), import.name(), import.ty()) {
+            ("env", "memory", ExternType::Memory(_)) => {
+                memory = true;
+                continue;
+            }
+            (module, field, ExternType::Func(func_type)) => {
+                match signatures
+                    .iter()
+                    .find(|(m, n, _)| m == module && n == field)
+                {
+                    Some((_, _, expected)) if expected == func_type => continue,
+                    Some((_, _, expected)) => format!(
+                        "expected signature {:?} -> {:?}, found {:?} -> {:?}",
+                        expected.params(),
+                        expected.results(),
+                        func_type.params(),
+                        func_type.results()
+                    ),
+                    None => "unknown host function".to_string(),
+                }
+            }
+            (_, _, ty) => format!("unsupported import {:?}", ty),
+        };
+
+        result.push(InvalidImport { name, reason });
+    }
+
+    if !memory {
+        result.push(InvalidImport {
+            name: "env.memory".to_string(),
+            reason: "memory import is missing".to_string(),
+        });
+    }
+
+    match module.get_export("__heap_base") {
+        Some(ExternType::Global(global)) if global.content() == ValueType::I32 => (),
+        Some(ty) => result.push(InvalidImport {
+            name: "__heap_base".to_string(),
+            reason: format!("expected i32 global, found {:?}", ty),
+        }),
+        None => result.push(InvalidImport {
+            name: "__heap_base".to_string(),
+            reason: "global export is missing".to_string(),
+        }),
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{exec::Executable, tests::wat2wasm, vm::config::StackConfig};
+    use wasmi::FuncType;
+
+    #[test]
+    fn test_signature_mismatch() {
+        let wat = r#"
+        (module
+            (import "env" "memory" (memory 1 1))
+            (import "env0" "get_value" (func $get_value (param i32) (result i64)))
+            (import "env0" "set_value" (func $set_value (param i64)))
+
+            (func (export "_constructor") (result i32)
+                (i32.const 0)
+            )
+
+            (global $__heap_base (export "__heap_base") i32 (i32.const 0))
+        )
+        "#;
+
+        let bytecode = wat2wasm(wat).expect("WAT code parsing failed");
+        let engine = Executable::engine(&StackConfig::default()).expect("Engine failed");
+        let (module, _) = Executable::compile(&engine, &bytecode).expect("Compilation failed");
+
+        let signatures: Vec<Signature> = vec![
+            (
+                "env0".to_string(),
+                "get_value".to_string(),
+                FuncType::new([ValueType::I32, ValueType::I32], [ValueType::I64]),
+            ),
+            (
+                "env0".to_string(),
+                "set_value".to_string(),
+                FuncType::new([ValueType::I64], []),
+            ),
+        ];
+
+        assert_eq!(
+            check(&module, &signatures),
+            vec![InvalidImport {
+                name: "env0.get_value".to_string(),
+                reason: "expected signature [I32, I32] -> [I64], found [I32] -> [I64]".to_string(),
+            }]
+        );
+
+        assert_eq!(
+            check(&module, &signatures[1..])[0].to_string(),
+            "env0.get_value: unknown host function"
+        );
+    }
+}
@@ -0,0 +1,455 @@
+use crate::{
+    error::{Error, ExecutableError, Result},
+    MEMORY,
+};
+use std::collections::BTreeSet;
+use wasmparser_nostd::{ExternalKind, Operator, Parser, Payload, TypeRef};
+
+/// Limits the bytecode is checked against.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LintLimits {
+    /// Maximum number of pages of memory, initial and maximum.
+    pub max_memory_pages: u32,
+    /// Maximum number of elements of a table, initial and maximum.
+    pub max_table_size: u32,
+    /// Maximum size of a data segment in bytes.
+    pub max_data_segment_size: u32,
+    /// Maximum number of locals of a function, not counting parameters.
+    pub max_locals: u32,
+    /// Maximum number of functions defined by the module.
+    pub max_functions: u32,
+}
+
+impl Default for LintLimits {
+    fn default() -> Self {
+        Self {
+            max_memory_pages: MEMORY.1,
+            max_table_size: 1024,
+            max_data_segment_size: 64 * 1024,
+            max_locals: 1024,
+            max_functions: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Severity {
+    /// The construct is allowed, but may behave unexpectedly.
+    Warning = 0,
+    /// The contract is rejected.
+    Error = 1,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum IssueKind {
+    /// The start function, or a function it calls, calls a host function.
+    StartCallsHost = 1,
+    /// The start function, or a function it calls, makes an indirect call.
+    StartCallsIndirect = 2,
+    /// The module has a start function, executed on each instantiation.
+    StartFunction = 3,
+    MutableGlobalExport = 4,
+    MemoryLimit = 5,
+    /// Memory without the maximum, its growth is only limited by the host.
+    UnboundedMemory = 6,
+    TableLimit = 7,
+    DataSegmentLimit = 8,
+    LocalsLimit = 9,
+    FunctionsLimit = 10,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+/// Constructs of the bytecode that may break deterministic execution or exceed the limits.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct LintReport {
+    pub issues: Vec<Issue>,
+}
+
+impl LintReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error)
+    }
+
+    /// Messages of the errors joined into one.
+    pub fn errors(&self) -> String {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .map(|issue| issue.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Getting byte representation of the report.
+    ///
+    /// The `u16` count of issues, each issue given by `severity: u8`, `kind: u8`
+    /// and the message prefixed with the `u16` length.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut result = (self.issues.len() as u16).to_be_bytes().to_vec();
+
+        for issue in self.issues.iter() {
+            result.push(issue.severity as u8);
+            result.push(issue.kind as u8);
+            result.extend_from_slice(&(issue.message.len() as u16).to_be_bytes());
+            result.extend_from_slice(issue.message.as_bytes());
+        }
+
+        result
+    }
+
+    fn push(&mut self, severity: Severity, kind: IssueKind, message: String) {
+        self.issues.push(Issue {
+            severity,
+            kind,
+            message,
+        });
+    }
+}
+
+/// Static analysis of the bytecode.
+pub fn lint(bytecode: &[u8], limits: &LintLimits) -> Result<LintReport> {
+    let mut report = LintReport::default();
+
+    let mut imported_functions: u32 = 0;
+    // Mutability of each global, imported globals first
+    let mut globals: Vec<bool> = vec![];
+    let mut start = None;
+    // Functions called directly by each defined function, and whether it calls indirectly
+    let mut calls: Vec<(Vec<u32>, bool)> = vec![];
+
+    for payload in Parser::new(0).parse_all(bytecode) {
+        match payload.map_err(invalid_bytecode)? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(invalid_bytecode)?;
+                    match import.ty {
+                        TypeRef::Func(_) => imported_functions += 1,
+                        TypeRef::Memory(memory) => {
+                            check_memory(&mut report, limits, memory.initial, memory.maximum)
+                        }
+                        TypeRef::Table(table) => {
+                            check_table(&mut report, limits, table.initial, table.maximum)
+                        }
+                        TypeRef::Global(global) => globals.push(global.mutable),
+                        _ => (),
+                    }
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                let functions = reader.count();
+                if functions > limits.max_functions {
+                    report.push(
+                        Severity::Error,
+                        IssueKind::FunctionsLimit,
+                        format!(
+                            "Module defines {} functions, the limit is {}",
+                            functions, limits.max_functions
+                        ),
+                    );
+                }
+            }
+            Payload::TableSection(reader) => {
+                for table in reader {
+                    let table = table.map_err(invalid_bytecode)?;
+                    check_table(&mut report, limits, table.initial, table.maximum);
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    let memory = memory.map_err(invalid_bytecode)?;
+                    check_memory(&mut report, limits, memory.initial, memory.maximum);
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    globals.push(global.map_err(invalid_bytecode)?.ty.mutable);
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.map_err(invalid_bytecode)?;
+                    if export.kind == ExternalKind::Global
+                        && globals.get(export.index as usize) == Some(&true)
+                    {
+                        report.push(
+                            Severity::Error,
+                            IssueKind::MutableGlobalExport,
+                            format!(
+                                "Mutable global {} is exported as {}",
+                                export.index, export.name
+                            ),
+                        );
+                    }
+                }
+            }
+            Payload::StartSection { func, .. } => {
+                start = Some(func);
+                report.push(
+                    Severity::Warning,
+                    IssueKind::StartFunction,
+                    format!("Function {} is executed on each instantiation", func),
+                );
+            }
+            Payload::DataSection(reader) => {
+                for (index, data) in reader.into_iter().enumerate() {
+                    let data = data.map_err(invalid_bytecode)?;
+                    if data.data.len() > limits.max_data_segment_size as usize {
+                        report.push(
+                            Severity::Error,
+                            IssueKind::DataSegmentLimit,
+                            format!(
+                                "Data segment {} has {} bytes, the limit is {}",
+                                index,
+                                data.data.len(),
+                                limits.max_data_segment_size
+                            ),
+                        );
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let index = imported_functions + calls.len() as u32;
+
+                let mut locals: u64 = 0;
+                for item in body.get_locals_reader().map_err(invalid_bytecode)? {
+                    let (count, _) = item.map_err(invalid_bytecode)?;
+                    locals += count as u64;
+                }
+
+                if locals > limits.max_locals as u64 {
+                    report.push(
+                        Severity::Error,
+                        IssueKind::LocalsLimit,
+                        format!(
+                            "Function {} has {} locals, the limit is {}",
+                            index, locals, limits.max_locals
+                        ),
+                    );
+                }
+
+                let mut direct = vec![];
+                let mut indirect = false;
+                for operator in body.get_operators_reader().map_err(invalid_bytecode)? {
+                    match operator.map_err(invalid_bytecode)? {
+                        Operator::Call { function_index } => direct.push(function_index),
+                        Operator::CallIndirect { .. } => indirect = true,
+                        _ => (),
+                    }
+                }
+
+                calls.push((direct, indirect));
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(start) = start {
+        check_start(&mut report, start, imported_functions, &calls);
+    }
+
+    Ok(report)
+}
+
+/// Checking the functions reachable from the start function by direct calls.
+fn check_start(
+    report: &mut LintReport,
+    start: u32,
+    imported_functions: u32,
+    calls: &[(Vec<u32>, bool)],
+) {
+    let mut visited = BTreeSet::new();
+    let mut stack = vec![start];
+
+    while let Some(index) = stack.pop() {
+        if !visited.insert(index) {
+            continue;
+        }
+
+        if index < imported_functions {
+            report.push(
+                Severity::Error,
+                IssueKind::StartCallsHost,
+                format!("Start function reaches host function {}", index),
+            );
+            continue;
+        }
+
+        if let Some((direct, indirect)) = calls.get((index - imported_functions) as usize) {
+            if *indirect {
+                report.push(
+                    Severity::Error,
+                    IssueKind::StartCallsIndirect,
+                    format!(
+                        "Start function reaches an indirect call in function {}",
+                        index
+                    ),
+                );
+            }
+            stack.extend(direct);
+        }
+    }
+}
+
+fn check_memory(report: &mut LintReport, limits: &LintLimits, initial: u64, maximum: Option<u64>) {
+    let max_pages = limits.max_memory_pages as u64;
+
+    match maximum {
+        Some(maximum) if maximum > max_pages => report.push(
+            Severity::Error,
+            IssueKind::MemoryLimit,
+            format!(
+                "Memory maximum is {} pages, the limit is {}",
+                maximum, max_pages
+            ),
+        ),
+        Some(_) => (),
+        None => report.push(
+            Severity::Warning,
+            IssueKind::UnboundedMemory,
+            format!(
+                "Memory has no maximum, it is limited by the host to {} pages",
+                max_pages
+            ),
+        ),
+    }
+
+    if initial > max_pages {
+        report.push(
+            Severity::Error,
+            IssueKind::MemoryLimit,
+            format!(
+                "Memory initial size is {} pages, the limit is {}",
+                initial, max_pages
+            ),
+        );
+    }
+}
+
+fn check_table(report: &mut LintReport, limits: &LintLimits, initial: u32, maximum: Option<u32>) {
+    let size = maximum.unwrap_or(initial).max(initial);
+
+    if maximum.is_none() || size > limits.max_table_size {
+        report.push(
+            Severity::Error,
+            IssueKind::TableLimit,
+            format!(
+                "Table size is {}, the limit is {}",
+                match maximum {
+                    Some(maximum) => maximum.to_string(),
+                    None => "unbounded".to_string(),
+                },
+                limits.max_table_size
+            ),
+        );
+    }
+}
+
+fn invalid_bytecode(error: wasmparser_nostd::BinaryReaderError) -> Error {
+    Error::Executable(ExecutableError::InvalidBytecode(format!("{:?}", error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::wat2wasm;
+
+    fn kinds(report: &LintReport) -> Vec<(Severity, IssueKind)> {
+        report
+            .issues
+            .iter()
+            .map(|issue| (issue.severity, issue.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint() {
+        let wat = r#"
+        (module
+            (import "env0" "call_arg_int" (func $call_arg_int (param i64)))
+            (import "env" "memory" (memory 1 32))
+
+            (table 2 funcref)
+
+            (func $init
+                (call $helper)
+            )
+
+            (func $helper
+                (call $call_arg_int (i64.const 1))
+            )
+
+            (func (export "_constructor") (result i32)
+                (local i64 i64 i64)
+                (i32.const 0)
+            )
+
+            (start $init)
+
+            (global $counter (export "counter") (mut i32) (i32.const 0))
+            (global $__heap_base (export "__heap_base") i32 (i32.const 4))
+
+            (data (i32.const 0) "data")
+        )
+        "#;
+
+        let bytecode = wat2wasm(wat).expect("WAT code parsing failed");
+        let limits = LintLimits {
+            max_data_segment_size: 3,
+            max_locals: 2,
+            max_functions: 2,
+            ..Default::default()
+        };
+
+        let report = lint(&bytecode, &limits).expect("Lint failed");
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (Severity::Error, IssueKind::MemoryLimit),
+                (Severity::Error, IssueKind::FunctionsLimit),
+                (Severity::Error, IssueKind::TableLimit),
+                (Severity::Error, IssueKind::MutableGlobalExport),
+                (Severity::Warning, IssueKind::StartFunction),
+                (Severity::Error, IssueKind::LocalsLimit),
+                (Severity::Error, IssueKind::DataSegmentLimit),
+                (Severity::Error, IssueKind::StartCallsHost),
+            ]
+        );
+        assert_eq!(
+            report.issues[3].message,
+            "Mutable global 0 is exported as counter"
+        );
+        assert!(report.has_errors());
+
+        let bytes = report.as_bytes();
+        assert_eq!(bytes[..2], [0, 8]);
+        assert_eq!(bytes[2..4], [1, IssueKind::MemoryLimit as u8]);
+
+        let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+
+            (func (export "_constructor") (result i32)
+                (i32.const 0)
+            )
+        )
+        "#;
+
+        let bytecode = wat2wasm(wat).expect("WAT code parsing failed");
+        let report = lint(&bytecode, &LintLimits::default()).expect("Lint failed");
+        assert_eq!(
+            kinds(&report),
+            vec![(Severity::Warning, IssueKind::UnboundedMemory)]
+        );
+        assert!(!report.has_errors());
+
+        assert!(lint(&[0, 97, 115, 109, 1, 0, 0, 0, 1], &LintLimits::default()).is_err());
+    }
+}
@@ -0,0 +1,422 @@
+use crate::{
+    error::Result,
+    exec::{abi::Abi, Executable},
+    vm::config::StackConfig,
+};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError},
+};
+use wasmi::Module;
+
+/// Hash of the bytecode the module was compiled from and the limits of the Wasm stack.
+type Key = [u8; 32];
+
+/// Limits of the module cache.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CacheLimits {
+    /// Maximum number of cached contracts.
+    pub max_entries: usize,
+    /// Maximum total size of the bytecode of cached modules, counted for each module.
+    pub max_bytes: usize,
+    /// Maximum number of modules of a contract, one is used by each running execution.
+    pub max_instances: usize,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 256,
+            max_bytes: 64 * 1024 * 1024,
+            max_instances: 8,
+        }
+    }
+}
+
+/// Counters of the module cache.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct CacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    /// Number of times all cached modules of a contract were in use and another one was compiled.
+    pub busy: u64,
+    /// Number of cached contracts.
+    pub entries: usize,
+    /// Total size of the bytecode of cached modules.
+    pub bytes: usize,
+}
+
+/// Module compiled from the bytecode together with the ABI read from its custom section.
+#[derive(Debug, Clone)]
+pub struct Compiled {
+    pub module: Arc<Module>,
+    pub abi: Option<Arc<Abi>>,
+}
+
+impl Compiled {
+    fn new(stack: &StackConfig, bytecode: &[u8]) -> Result<Self> {
+        let (module, abi) = Executable::compile(&Executable::engine(stack)?, bytecode)?;
+
+        Ok(Self {
+            module: Arc::new(module),
+            abi: abi.map(Arc::new),
+        })
+    }
+
+    /// Compiling another module of the cached contract, the bytecode and the ABI are already validated.
+    fn instance(stack: &StackConfig, bytecode: &[u8], abi: Option<Arc<Abi>>) -> Result<Self> {
+        let module = Executable::compile_module(&Executable::engine(stack)?, bytecode)?;
+
+        Ok(Self {
+            module: Arc::new(module),
+            abi,
+        })
+    }
+}
+
+/// Result of a cache lookup.
+enum Lookup {
+    Hit(Compiled),
+    /// All modules of the contract are in use, the ABI is shared by a new one.
+    Busy(Option<Arc<Abi>>),
+    Miss,
+}
+
+/// Modules compiled from the same bytecode.
+struct Entry {
+    modules: Vec<Arc<Module>>,
+    abi: Option<Arc<Abi>>,
+    size: usize,
+    last_used: u64,
+}
+
+impl Entry {
+    /// Total size of the bytecode of the modules.
+    fn bytes(&self) -> usize {
+        self.size * self.modules.len()
+    }
+}
+
+/// LRU cache of validated modules keyed by the hash of the bytecode and the stack limits.
+///
+/// Each module is compiled by its own `Engine`. `wasmi` holds a read lock on the engine
+/// for the whole execution, while compilation and instantiation take a write lock.
+/// So a shared engine would deadlock on a contract called from a running contract,
+/// and a module is only given out while it is not used by another execution.
+/// A contract used by several executions at once keeps a pool of modules, so only
+/// the first such use compiles it again. Besides, the code compiled by an engine
+/// is only freed together with it.
+pub struct ModuleCache {
+    limits: CacheLimits,
+    entries: HashMap<Key, Entry>,
+    tick: u64,
+    metrics: CacheMetrics,
+}
+
+impl ModuleCache {
+    pub fn new(limits: CacheLimits) -> Self {
+        Self {
+            limits,
+            entries: Default::default(),
+            tick: 0,
+            metrics: Default::default(),
+        }
+    }
+
+    /// Getting a free module compiled from the bytecode, compiling it if there is none.
+    pub fn get_or_compile(&mut self, bytecode: &[u8], stack: &StackConfig) -> Result<Compiled> {
+        let key = Self::key(bytecode, stack);
+
+        let compiled = match self.get(&key) {
+            Lookup::Hit(compiled) => return Ok(compiled),
+            Lookup::Busy(abi) => Compiled::instance(stack, bytecode, abi)?,
+            Lookup::Miss => Compiled::new(stack, bytecode)?,
+        };
+        self.insert(key, compiled.clone(), bytecode.len());
+
+        Ok(compiled)
+    }
+
+    /// Setting the limits of the cache, evicting modules that no longer fit.
+    pub fn set_limits(&mut self, limits: CacheLimits) {
+        self.limits = limits;
+        self.evict(0, 0);
+    }
+
+    pub fn metrics(&self) -> CacheMetrics {
+        self.metrics
+    }
+
+    /// Removing all modules.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.metrics.entries = 0;
+        self.metrics.bytes = 0;
+    }
+
+    fn key(bytecode: &[u8], stack: &StackConfig) -> Key {
+        let mut hasher: Blake2b<U32> = Blake2b::new();
+        hasher.update(bytecode);
+        for value in [
+            stack.initial_value_stack_height,
+            stack.maximum_value_stack_height,
+            stack.maximum_recursion_depth,
+        ] {
+            hasher.update((value as u64).to_be_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    fn get(&mut self, key: &Key) -> Lookup {
+        self.tick += 1;
+
+        let entry = match self.entries.get_mut(key) {
+            Some(entry) => entry,
+            None => {
+                self.metrics.misses += 1;
+                return Lookup::Miss;
+            }
+        };
+        entry.last_used = self.tick;
+
+        // The cache holds the only reference when the module is not in use
+        match entry
+            .modules
+            .iter()
+            .find(|module| Arc::strong_count(module) == 1)
+        {
+            Some(module) => {
+                self.metrics.hits += 1;
+                Lookup::Hit(Compiled {
+                    module: module.clone(),
+                    abi: entry.abi.clone(),
+                })
+            }
+            None => {
+                self.metrics.busy += 1;
+                Lookup::Busy(entry.abi.clone())
+            }
+        }
+    }
+
+    fn insert(&mut self, key: Key, compiled: Compiled, size: usize) {
+        if size > self.limits.max_bytes || self.limits.max_entries == 0 {
+            return;
+        }
+
+        if let Some(entry) = self.entries.get(&key) {
+            if entry.modules.len() >= self.limits.max_instances {
+                return;
+            }
+
+            self.evict(0, size);
+
+            // The entry itself may be evicted to make room for the module
+            if let Some(entry) = self.entries.get_mut(&key) {
+                entry.modules.push(compiled.module);
+                self.metrics.bytes += size;
+                return;
+            }
+        }
+
+        self.evict(1, size);
+
+        self.tick += 1;
+        let entry = Entry {
+            modules: vec![compiled.module],
+            abi: compiled.abi,
+            size,
+            last_used: self.tick,
+        };
+
+        self.entries.insert(key, entry);
+        self.metrics.entries += 1;
+        self.metrics.bytes += size;
+    }
+
+    /// Evicting the least recently used contracts until the given number of contracts
+    /// with the given total size fits into the limits.
+    fn evict(&mut self, entries: usize, bytes: usize) {
+        while !self.entries.is_empty()
+            && (self.entries.len() + entries > self.limits.max_entries
+                || self.metrics.bytes + bytes > self.limits.max_bytes)
+        {
+            let key = match self.entries.iter().min_by_key(|(_, entry)| entry.last_used) {
+                Some((key, _)) => *key,
+                None => break,
+            };
+
+            if let Some(entry) = self.entries.remove(&key) {
+                self.metrics.entries -= 1;
+                self.metrics.bytes -= entry.bytes();
+                self.metrics.evictions += 1;
+            }
+        }
+    }
+}
+
+/// Process-wide module cache.
+fn cache() -> MutexGuard<'static, ModuleCache> {
+    static CACHE: OnceLock<Mutex<ModuleCache>> = OnceLock::new();
+
+    CACHE
+        .get_or_init(|| Mutex::new(ModuleCache::new(CacheLimits::default())))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Getting the module compiled from the bytecode with the stack limits from the process-wide cache.
+pub fn load(bytecode: &[u8], stack: &StackConfig) -> Result<Compiled> {
+    let key = ModuleCache::key(bytecode, stack);
+
+    // The lock is not held while compiling
+    let lookup = cache().get(&key);
+    let compiled = match lookup {
+        Lookup::Hit(compiled) => return Ok(compiled),
+        Lookup::Busy(abi) => Compiled::instance(stack, bytecode, abi)?,
+        Lookup::Miss => Compiled::new(stack, bytecode)?,
+    };
+    cache().insert(key, compiled.clone(), bytecode.len());
+
+    Ok(compiled)
+}
+
+/// Setting the limits of the process-wide module cache.
+pub fn set_limits(limits: CacheLimits) {
+    cache().set_limits(limits)
+}
+
+/// Getting the counters of the process-wide module cache.
+pub fn metrics() -> CacheMetrics {
+    cache().metrics()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, tests::wat2wasm};
+
+    fn contract(value: i32) -> Vec<u8> {
+        wat2wasm(&format!(
+            r#"
+            (module
+                (func (export "_constructor") (result i32)
+                    (i32.const {})
+                )
+            )
+            "#,
+            value
+        ))
+        .expect("WAT code parsing failed")
+    }
+
+    #[test]
+    fn test_module_cache() {
+        let (first, second, third) = (contract(1), contract(2), contract(3));
+        let stack = StackConfig::default();
+
+        let mut cache = ModuleCache::new(CacheLimits {
+            max_entries: 2,
+            ..Default::default()
+        });
+
+        cache
+            .get_or_compile(&first, &stack)
+            .expect("Compilation failed");
+        cache
+            .get_or_compile(&second, &stack)
+            .expect("Compilation failed");
+        cache
+            .get_or_compile(&first, &stack)
+            .expect("Compilation failed");
+
+        let metrics = cache.metrics();
+        assert_eq!((metrics.hits, metrics.misses), (1, 2));
+        assert_eq!(metrics.entries, 2);
+        assert_eq!(metrics.bytes, first.len() + second.len());
+
+        // The least recently used module is evicted
+        cache
+            .get_or_compile(&third, &stack)
+            .expect("Compilation failed");
+        cache
+            .get_or_compile(&first, &stack)
+            .expect("Compilation failed");
+        cache
+            .get_or_compile(&second, &stack)
+            .expect("Compilation failed");
+
+        let metrics = cache.metrics();
+        assert_eq!((metrics.hits, metrics.misses), (2, 4));
+        assert_eq!(metrics.evictions, 2);
+        assert_eq!(metrics.entries, 2);
+
+        // A module in use is compiled again and kept in the pool of the contract
+        let module = cache
+            .get_or_compile(&second, &stack)
+            .expect("Compilation failed");
+        let copy = cache
+            .get_or_compile(&second, &stack)
+            .expect("Compilation failed");
+        assert!(!Arc::ptr_eq(&module.module, &copy.module));
+        assert_eq!(cache.metrics().busy, 1);
+        assert_eq!(cache.metrics().entries, 2);
+        assert_eq!(cache.metrics().bytes, first.len() + 2 * second.len());
+        let pooled = [Arc::as_ptr(&module.module), Arc::as_ptr(&copy.module)];
+        drop((module, copy));
+
+        // Both modules are reused afterwards
+        let hits = cache.metrics().hits;
+        let module = cache
+            .get_or_compile(&second, &stack)
+            .expect("Compilation failed");
+        let copy = cache
+            .get_or_compile(&second, &stack)
+            .expect("Compilation failed");
+        assert!(pooled.contains(&Arc::as_ptr(&module.module)));
+        assert!(pooled.contains(&Arc::as_ptr(&copy.module)));
+        assert_eq!(cache.metrics().hits, hits + 2);
+        assert_eq!(cache.metrics().busy, 1);
+        drop((module, copy));
+
+        // The number of modules of a contract is limited
+        cache.set_limits(CacheLimits {
+            max_entries: 2,
+            max_instances: 1,
+            ..Default::default()
+        });
+        let module = cache
+            .get_or_compile(&first, &stack)
+            .expect("Compilation failed");
+        let bytes = cache.metrics().bytes;
+        let copy = cache
+            .get_or_compile(&first, &stack)
+            .expect("Compilation failed");
+        assert!(!Arc::ptr_eq(&module.module, &copy.module));
+        assert_eq!(cache.metrics().bytes, bytes);
+        drop((module, copy));
+
+        // Modules compiled with other stack limits are cached separately
+        let other = StackConfig {
+            maximum_recursion_depth: 16,
+            ..stack
+        };
+        let misses = cache.metrics().misses;
+        cache
+            .get_or_compile(&second, &other)
+            .expect("Compilation failed");
+        assert_eq!(cache.metrics().misses, misses + 1);
+
+        let result = cache.get_or_compile(&[0, 1, 2], &stack);
+        assert!(matches!(result, Err(Error::Executable(_))));
+        assert_eq!(cache.metrics().entries, 2);
+
+        cache.set_limits(CacheLimits {
+            max_bytes: first.len(),
+            ..Default::default()
+        });
+        assert_eq!(cache.metrics().entries, 1);
+    }
+}
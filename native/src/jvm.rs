@@ -1,9 +1,13 @@
 use crate::{
     error::{Error, JvmError, Result},
     node::Node,
-    vm::Vm,
+    runtime::utils,
+    vm::{error_report::ErrorReport, FrameFuel},
+};
+use jni::{
+    objects::{GlobalRef, JByteArray, JObject, JValue},
+    JavaVM,
 };
-use jni::objects::{JByteArray, JObject, JValue};
 use log::error;
 
 // A primitive java type.
@@ -16,12 +20,10 @@ use log::error;
 
 macro_rules! env {
     ($self:expr) => {{
-        match &$self.jvm {
-            Some(jvm) => jvm
-                .attach_current_thread()
-                .map_err(|_| Error::Jvm(JvmError::AttachCurrentThread))?,
-            None => return Err(Error::Jvm(JvmError::JvmNotFound)),
-        }
+        $self
+            .jvm
+            .attach_current_thread()
+            .map_err(|_| Error::Jvm(JvmError::AttachCurrentThread))?
     }};
 }
 
@@ -32,21 +34,91 @@ macro_rules! byte_array {
     }};
 }
 
-macro_rules! jvm_callback {
-    ($jvm_callback:expr) => {
-        match $jvm_callback {
-            Some(jvm_callback) => jvm_callback,
-            None => return Err(Error::Jvm(JvmError::JvmCallbackNotFound)),
-        }
-    };
+/// Node interface implemented through JNI calls to the `WASMService` callback.
+pub struct Jvm {
+    jvm: JavaVM,
+    callback: GlobalRef,
+}
+
+impl Jvm {
+    pub fn new(jvm: JavaVM, callback: GlobalRef) -> Self {
+        Self { jvm, callback }
+    }
+
+    /// Reporting the fuel consumed by a frame of the call stack.
+    pub fn fuel_consumed(&self, frame: &FrameFuel) -> Result<()> {
+        let mut env = env!(self);
+
+        let contract_id = byte_array!(env, &frame.contract_id);
+        let func_name = env
+            .new_string(&frame.func_name)
+            .map_err(|_| Error::Jvm(JvmError::NewString))?;
+
+        env.call_method(
+            &self.callback,
+            "fuelConsumed",
+            "([BLjava/lang/String;IJ)V",
+            &[
+                JValue::Object(&contract_id.into()),
+                JValue::Object(&func_name.into()),
+                JValue::Int(frame.depth as i32),
+                JValue::Long(frame.fuel_consumed as i64),
+            ],
+        )
+        .map_err(|error| {
+            // Reports are not part of the result, the exception must not reach the executor
+            let _ = env.exception_clear();
+            let err = JvmError::MethodCall(format!("{:?}", error));
+            error!("{}", error);
+            Error::Jvm(err)
+        })?;
+
+        Ok(())
+    }
+
+    /// Reporting the failure of the execution.
+    pub fn execution_failed(&self, report: &ErrorReport) -> Result<()> {
+        let mut env = env!(self);
+
+        let category = env
+            .new_string(report.category.as_str())
+            .map_err(|_| Error::Jvm(JvmError::NewString))?;
+        let message = env
+            .new_string(&report.message)
+            .map_err(|_| Error::Jvm(JvmError::NewString))?;
+        let contract_id = byte_array!(env, report.contract_id());
+        let frames = byte_array!(env, &report.frames_as_bytes());
+
+        env.call_method(
+            &self.callback,
+            "executionFailed",
+            "(ILjava/lang/String;Ljava/lang/String;[B[B)V",
+            &[
+                JValue::Int(report.code),
+                JValue::Object(&category.into()),
+                JValue::Object(&message.into()),
+                JValue::Object(&contract_id.into()),
+                JValue::Object(&frames.into()),
+            ],
+        )
+        .map_err(|error| {
+            // Reports are not part of the result, the exception must not reach the executor
+            let _ = env.exception_clear();
+            let err = JvmError::MethodCall(format!("{:?}", error));
+            error!("{}", error);
+            Error::Jvm(err)
+        })?;
+
+        Ok(())
+    }
 }
 
 // Implementing the JVM call
-impl Node for Vm {
+impl Node for Jvm {
     fn get_chain_id(&self) -> Result<i8> {
         let mut env = env!(self);
 
-        env.call_method(jvm_callback!(&self.jvm_callback), "getChainId", "()B", &[])
+        env.call_method(&self.callback, "getChainId", "()B", &[])
             .map_err(|error| {
                 let err = JvmError::MethodCall(format!("{:?}", error));
                 error!("{}", error);
@@ -62,7 +134,7 @@ impl Node for Vm {
         let message = byte_array!(env, message);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "require",
             "([B)V",
             &[JValue::Object(&message.into())],
@@ -83,7 +155,7 @@ impl Node for Vm {
 
         let result = env
             .call_method(
-                jvm_callback!(&self.jvm_callback),
+                &self.callback,
                 "getBytecode",
                 "([B)[B",
                 &[JValue::Object(&contract_id.into())],
@@ -111,7 +183,7 @@ impl Node for Vm {
         let payments = byte_array!(env, payments);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "addPayments",
             "([B[B[B)V",
             &[
@@ -137,7 +209,7 @@ impl Node for Vm {
         let address = byte_array!(env, address);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "getBalance",
             "([B[B)J",
             &[
@@ -168,7 +240,7 @@ impl Node for Vm {
         let recipient = byte_array!(env, recipient);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "transfer",
             "([B[B[BJ)V",
             &[
@@ -204,7 +276,7 @@ impl Node for Vm {
 
         let result = env
             .call_method(
-                jvm_callback!(&self.jvm_callback),
+                &self.callback,
                 "issue",
                 "([B[B[BJJZ)[B",
                 &[
@@ -238,7 +310,7 @@ impl Node for Vm {
         let asset_id = byte_array!(env, asset_id);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "burn",
             "([B[BJ)V",
             &[
@@ -269,7 +341,7 @@ impl Node for Vm {
         let asset_id = byte_array!(env, asset_id);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "reissue",
             "([B[BJZ)V",
             &[
@@ -296,7 +368,7 @@ impl Node for Vm {
 
         let result = env
             .call_method(
-                jvm_callback!(&self.jvm_callback),
+                &self.callback,
                 "block",
                 "([B)[B",
                 &[JValue::Object(&field.into())],
@@ -324,7 +396,7 @@ impl Node for Vm {
 
         let result = env
             .call_method(
-                jvm_callback!(&self.jvm_callback),
+                &self.callback,
                 "fastHash",
                 "([B)[B",
                 &[JValue::Object(&bytes.into())],
@@ -351,7 +423,7 @@ impl Node for Vm {
 
         let result = env
             .call_method(
-                jvm_callback!(&self.jvm_callback),
+                &self.callback,
                 "secureHash",
                 "([B)[B",
                 &[JValue::Object(&bytes.into())],
@@ -379,7 +451,7 @@ impl Node for Vm {
         let public_key = byte_array!(env, public_key);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "sigVerify",
             "([B[B[B)Z",
             &[
@@ -406,7 +478,7 @@ impl Node for Vm {
 
         let result = env
             .call_method(
-                jvm_callback!(&self.jvm_callback),
+                &self.callback,
                 "lease",
                 "([B[BJ)[B",
                 &[
@@ -437,7 +509,7 @@ impl Node for Vm {
         let lease_id = byte_array!(env, lease_id);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "cancelLease",
             "([B[B)V",
             &[
@@ -462,7 +534,7 @@ impl Node for Vm {
         let key = byte_array!(env, key);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "containsKey",
             "([B[B)Z",
             &[JValue::Object(&address.into()), JValue::Object(&key.into())],
@@ -484,7 +556,7 @@ impl Node for Vm {
 
         let result = env
             .call_method(
-                jvm_callback!(&self.jvm_callback),
+                &self.callback,
                 "getStorage",
                 "([B[B)[B",
                 &[JValue::Object(&address.into()), JValue::Object(&key.into())],
@@ -511,7 +583,7 @@ impl Node for Vm {
         let value = byte_array!(env, value);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "setStorage",
             "([B[B)V",
             &[
@@ -528,6 +600,94 @@ impl Node for Vm {
         Ok(())
     }
 
+    fn set_storage_batch(&self, contract_id: &[u8], values: &[u8]) -> Result<()> {
+        let mut env = env!(self);
+
+        let contract_id = byte_array!(env, contract_id);
+        let values = byte_array!(env, values);
+
+        env.call_method(
+            &self.callback,
+            "setStorageBatch",
+            "([B[B)V",
+            &[
+                JValue::Object(&contract_id.into()),
+                JValue::Object(&values.into()),
+            ],
+        )
+        .map_err(|error| {
+            let err = JvmError::MethodCall(format!("{:?}", error));
+            error!("{}", error);
+            Error::Jvm(err)
+        })?;
+
+        Ok(())
+    }
+
+    fn delete_storage(&self, contract_id: &[u8], key: &[u8]) -> Result<()> {
+        let mut env = env!(self);
+
+        let contract_id = byte_array!(env, contract_id);
+        let key = byte_array!(env, key);
+
+        env.call_method(
+            &self.callback,
+            "deleteStorage",
+            "([B[B)V",
+            &[
+                JValue::Object(&contract_id.into()),
+                JValue::Object(&key.into()),
+            ],
+        )
+        .map_err(|error| {
+            let err = JvmError::MethodCall(format!("{:?}", error));
+            error!("{}", error);
+            Error::Jvm(err)
+        })?;
+
+        Ok(())
+    }
+
+    fn storage_keys(
+        &self,
+        address: &[u8],
+        prefix: &[u8],
+        cursor: &[u8],
+        limit: u32,
+    ) -> Result<Vec<Vec<u8>>> {
+        let mut env = env!(self);
+
+        let address = byte_array!(env, address);
+        let prefix = byte_array!(env, prefix);
+        let cursor = byte_array!(env, cursor);
+
+        let result = env
+            .call_method(
+                &self.callback,
+                "storageKeys",
+                "([B[B[BI)[B",
+                &[
+                    JValue::Object(&address.into()),
+                    JValue::Object(&prefix.into()),
+                    JValue::Object(&cursor.into()),
+                    JValue::Int(limit as i32),
+                ],
+            )
+            .map_err(|error| {
+                let err = JvmError::MethodCall(format!("{:?}", error));
+                error!("{}", error);
+                Error::Jvm(err)
+            })?
+            .l()
+            .map_err(|_| Error::Jvm(JvmError::ReceiveObject))?;
+
+        let bytes = env
+            .convert_byte_array(<JObject<'_> as Into<JByteArray>>::into(result))
+            .map_err(|_| Error::Jvm(JvmError::ByteArrayConversion))?;
+
+        utils::get_list(&bytes, &mut 0)
+    }
+
     // Tx
     fn get_tx_payments(&self, payment_id: &[u8]) -> Result<i64> {
         let mut env = env!(self);
@@ -535,7 +695,7 @@ impl Node for Vm {
         let payment_id = byte_array!(env, payment_id);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "getTxPayments",
             "([B)J",
             &[JValue::Object(&payment_id.into())],
@@ -556,7 +716,7 @@ impl Node for Vm {
 
         let result = env
             .call_method(
-                jvm_callback!(&self.jvm_callback),
+                &self.callback,
                 "getTxPaymentAssetId",
                 "([BJ)[B",
                 &[JValue::Object(&payment_id.into()), number.into()],
@@ -582,7 +742,7 @@ impl Node for Vm {
         let payment_id = byte_array!(env, payment_id);
 
         env.call_method(
-            jvm_callback!(&self.jvm_callback),
+            &self.callback,
             "getTxPaymentAmount",
             "([BJ)J",
             &[JValue::Object(&payment_id.into()), number.into()],
@@ -603,7 +763,7 @@ impl Node for Vm {
 
         let result = env
             .call_method(
-                jvm_callback!(&self.jvm_callback),
+                &self.callback,
                 "tx",
                 "([B)[B",
                 &[JValue::Object(&field.into())],
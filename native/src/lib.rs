@@ -1,34 +1,45 @@
-#[cfg(feature = "jvm")]
+#[cfg(feature = "vm")]
 mod env;
 
-#[cfg(feature = "jvm")]
-mod error;
+#[cfg(feature = "vm")]
+pub mod error;
 
-#[cfg(feature = "jvm")]
-mod exec;
+#[cfg(feature = "vm")]
+pub mod exec;
 
 #[cfg(feature = "jvm")]
 mod jvm;
 
 mod modules;
 
-#[cfg(feature = "jvm")]
-mod node;
+#[cfg(feature = "vm")]
+pub mod node;
 
-#[cfg(feature = "jvm")]
-mod runtime;
+#[cfg(feature = "vm")]
+pub mod runtime;
 
-#[cfg(all(test, feature = "jvm"))]
+#[cfg(all(test, feature = "vm"))]
 mod tests;
 
-#[cfg(feature = "jvm")]
-mod vm;
+#[cfg(feature = "vm")]
+pub mod vm;
 
 pub use modules::v0;
 pub use modules::v1;
+pub use modules::v2;
+pub use modules::v3;
+pub use modules::v4;
+
+#[cfg(feature = "vm")]
+use crate::vm::cost_schedule::{Cost, CostSchedule};
 
 #[cfg(feature = "jvm")]
-use crate::{error::JvmError, exec::Executable, vm::Vm};
+use crate::{
+    error::{Error, ExecutableError, JvmError},
+    exec::{lint, module_cache, Executable},
+    jvm::Jvm,
+    vm::{config::VmConfig, error_report::ErrorReport, Vm},
+};
 #[cfg(feature = "jvm")]
 use base58::ToBase58;
 #[cfg(feature = "jvm")]
@@ -40,9 +51,11 @@ use jni::{
 #[cfg(feature = "jvm")]
 use log::{debug, error};
 #[cfg(feature = "jvm")]
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "jvm")]
 use wasmi::Value;
 
-/// Size of allocated linear memory.
+/// Default size of allocated linear memory.
 pub const MEMORY: (u32, u32) = (2, 16);
 
 // This `#[no_mangle]` keeps rust from "mangling" the name and making it unique
@@ -68,7 +81,117 @@ pub extern "system" fn Java_com_wavesenterprise_wasm_core_WASMExecutor_runContra
     func_name: JString<'local>,
     params: JByteArray<'local>,
     fuel_limit: jlong,
+    config: JByteArray<'local>,
+    callback: JObject<'local>,
+) -> jint {
+    run_contract(
+        &mut env,
+        contract_id,
+        bytecode,
+        func_name,
+        params,
+        fuel_limit,
+        config,
+        callback,
+        false,
+        None,
+    )
+}
+
+/// External Java function to execute bytecode contract in read-only mode.
+/// Any attempt of the contract to change the state fails with an error.
+#[cfg(feature = "jvm")]
+#[no_mangle]
+pub extern "system" fn Java_com_wavesenterprise_wasm_core_WASMExecutor_runContractReadOnly<
+    'local,
+>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    contract_id: JByteArray<'local>,
+    bytecode: JByteArray<'local>,
+    func_name: JString<'local>,
+    params: JByteArray<'local>,
+    fuel_limit: jlong,
+    config: JByteArray<'local>,
+    callback: JObject<'local>,
+) -> jint {
+    run_contract(
+        &mut env,
+        contract_id,
+        bytecode,
+        func_name,
+        params,
+        fuel_limit,
+        config,
+        callback,
+        true,
+        None,
+    )
+}
+
+/// External Java function to execute bytecode contract and get the execution result.
+/// Returns the error code as big-endian `i32`, followed by
+/// the serialized [`ExecutionResult`](crate::vm::execution_result::ExecutionResult)
+/// if the execution succeeded.
+#[cfg(feature = "jvm")]
+#[no_mangle]
+pub extern "system" fn Java_com_wavesenterprise_wasm_core_WASMExecutor_runContractWithResult<
+    'local,
+>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    contract_id: JByteArray<'local>,
+    bytecode: JByteArray<'local>,
+    func_name: JString<'local>,
+    params: JByteArray<'local>,
+    fuel_limit: jlong,
+    config: JByteArray<'local>,
     callback: JObject<'local>,
+) -> JByteArray<'local> {
+    let mut execution_result = vec![];
+
+    let code = run_contract(
+        &mut env,
+        contract_id,
+        bytecode,
+        func_name,
+        params,
+        fuel_limit,
+        config,
+        callback,
+        false,
+        Some(&mut execution_result),
+    );
+
+    let mut bytes = code.to_be_bytes().to_vec();
+    if code == 0 {
+        bytes.extend(execution_result);
+    }
+
+    match env.byte_array_from_slice(&bytes) {
+        Ok(array) => array,
+        Err(_) => {
+            error!("{}", JvmError::NewByteArray);
+            JByteArray::default()
+        }
+    }
+}
+
+/// Executing the contract with the serialized [`VmConfig`], the execution result
+/// is serialized if requested. In read-only mode contracts are not allowed to change the state.
+#[cfg(feature = "jvm")]
+#[allow(clippy::too_many_arguments)]
+fn run_contract<'local>(
+    env: &mut JNIEnv<'local>,
+    contract_id: JByteArray<'local>,
+    bytecode: JByteArray<'local>,
+    func_name: JString<'local>,
+    params: JByteArray<'local>,
+    fuel_limit: jlong,
+    config: JByteArray<'local>,
+    callback: JObject<'local>,
+    read_only: bool,
+    execution_result: Option<&mut Vec<u8>>,
 ) -> jint {
     let _ = env_logger::try_init();
     let contract_id = match env.convert_byte_array(contract_id) {
@@ -79,110 +202,336 @@ pub extern "system" fn Java_com_wavesenterprise_wasm_core_WASMExecutor_runContra
         }
     };
 
-    debug!(
-        "Started WEVM to execute the contract: {}",
-        contract_id.clone().to_base58()
-    );
+    // Panics must not unwind into the JVM
+    catch_panic(Some(&contract_id.clone()), move || {
+        debug!(
+            "Started WEVM to execute the contract: {}",
+            contract_id.clone().to_base58()
+        );
 
-    let bytecode = match env.convert_byte_array(bytecode) {
-        Ok(bytes) => bytes,
-        Err(_) => {
-            error!("{}", JvmError::ByteArrayConversion);
-            return JvmError::ByteArrayConversion.as_jint();
+        let bytecode = match env.convert_byte_array(bytecode) {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                error!("{}", JvmError::ByteArrayConversion);
+                return JvmError::ByteArrayConversion.as_jint();
+            }
+        };
+
+        let config = match env
+            .convert_byte_array(config)
+            .map_err(|_| Error::Jvm(JvmError::ByteArrayConversion))
+            .and_then(|bytes| VmConfig::deserialize(&bytes))
+        {
+            Ok(config) => config,
+            Err(error) => {
+                error!("{}", error);
+                return error.as_jint();
+            }
+        };
+
+        let (jvm, reporter_jvm) = match (env.get_java_vm(), env.get_java_vm()) {
+            (Ok(jvm), Ok(reporter_jvm)) => (jvm, reporter_jvm),
+            _ => {
+                error!("{}", JvmError::GetJavaVM);
+                return JvmError::GetJavaVM.as_jint();
+            }
+        };
+
+        let callback = match env.new_global_ref(callback) {
+            Ok(callback) => callback,
+            Err(_) => {
+                error!("{}", JvmError::NewGlobalRef);
+                return JvmError::NewGlobalRef.as_jint();
+            }
+        };
+
+        // Separate handle to the callback to report fuel after the VM is done
+        let reporter = Jvm::new(reporter_jvm, callback.clone());
+
+        // Costs passed by the node override the default ones
+        let mut schedule = cost_schedule();
+        for (module, name, cost) in config.costs.iter() {
+            schedule.set(module, name, *cost);
         }
-    };
 
-    let jvm = match env.get_java_vm() {
-        Ok(jvm) => jvm,
-        Err(_) => {
-            error!("{}", JvmError::GetJavaVM);
-            return JvmError::GetJavaVM.as_jint();
+        let mut vm = match Vm::new(
+            contract_id,
+            bytecode,
+            config,
+            fuel_limit as u64,
+            modules(),
+            schedule,
+            Jvm::new(jvm, callback),
+        ) {
+            Ok(vm) => vm,
+            Err(error) => {
+                error!("{}", error);
+                return error.as_jint();
+            }
+        };
+        vm.set_read_only(read_only);
+
+        let func_name: String = match env.get_string(&func_name) {
+            Ok(string) => string.into(),
+            Err(_) => {
+                error!("{}", JvmError::NewString);
+                return JvmError::NewString.as_jint();
+            }
+        };
+
+        let params = match env.convert_byte_array(params) {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                error!("{}", JvmError::ByteArrayConversion);
+                return JvmError::ByteArrayConversion.as_jint();
+            }
+        };
+
+        let result = vm.run(&func_name, &params);
+
+        debug!("Fuel consumed: {}", vm.fuel_consumed());
+        debug!("Module cache: {:?}", module_cache::metrics());
+
+        // Fuel is spent even if the execution failed.
+        // The changes are already committed, so a failure to report does not change the result
+        for frame in vm.fuel_report() {
+            if let Err(error) = reporter.fuel_consumed(frame) {
+                error!("{}", error);
+            }
         }
-    };
 
-    let callback = match env.new_global_ref(callback) {
-        Ok(callback) => callback,
-        Err(_) => {
-            error!("{}", JvmError::NewGlobalRef);
-            return JvmError::NewGlobalRef.as_jint();
+        let code = match &result {
+            Ok(result) => match result[0] {
+                Value::I32(value) => value as jint,
+                _ => 0 as jint,
+            },
+            Err(error) => {
+                error!("{}", error);
+                error.as_jint()
+            }
+        };
+
+        // The node receives the messages and the call stack along with the error code
+        if code != 0 {
+            if let Some(report) = vm.error_report().filter(|report| report.code == code) {
+                if let Err(error) = reporter.execution_failed(report) {
+                    error!("{}", error);
+                }
+            }
         }
-    };
 
-    let mut vm = match Vm::new(
-        contract_id,
-        bytecode,
-        MEMORY,
-        fuel_limit as u64,
-        modules(),
-        Some(jvm),
-        Some(callback),
-    ) {
-        Ok(vm) => vm,
-        Err(error) => {
-            error!("{}", error);
-            return error.as_jint();
+        if result.is_err() {
+            return code;
         }
-    };
 
-    let func_name: String = match env.get_string(&func_name) {
-        Ok(string) => string.into(),
-        Err(_) => {
-            error!("{}", JvmError::NewString);
-            return JvmError::NewString.as_jint();
+        if let Some(output) = execution_result {
+            match vm.execution_result() {
+                Ok(result) => *output = result.as_bytes(),
+                Err(error) => {
+                    error!("{}", error);
+                    return error.as_jint();
+                }
+            }
         }
-    };
 
-    let params = match env.convert_byte_array(params) {
-        Ok(bytes) => bytes,
-        Err(_) => {
-            error!("{}", JvmError::ByteArrayConversion);
-            return JvmError::ByteArrayConversion.as_jint();
+        code
+    })
+}
+
+/// External Java function to validate bytecode contract with the serialized [`VmConfig`].
+/// A failure is also reported to the callback with `executionFailed`,
+/// for invalid imports the message lists each of them.
+#[cfg(feature = "jvm")]
+#[no_mangle]
+pub extern "system" fn Java_com_wavesenterprise_wasm_core_WASMExecutor_validateBytecode<'local>(
+    env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    bytecode: JByteArray<'local>,
+    config: JByteArray<'local>,
+    callback: JObject<'local>,
+) -> jint {
+    catch_panic(None, || {
+        let result = env
+            .convert_byte_array(bytecode)
+            .map_err(|_| Error::Jvm(JvmError::ByteArrayConversion))
+            .and_then(|bytecode| {
+                let config = env
+                    .convert_byte_array(config)
+                    .map_err(|_| Error::Jvm(JvmError::ByteArrayConversion))
+                    .and_then(|bytes| VmConfig::deserialize(&bytes))?;
+
+                Executable::validate_bytecode(&bytecode, &config)
+            });
+
+        match result {
+            Ok(_) => 0,
+            Err(error) => {
+                error!("{}", error);
+                report_failure(&env, &callback, &error);
+                error.as_jint()
+            }
         }
-    };
+    })
+}
 
-    let result = match vm.run(&func_name, &params) {
-        Ok(result) => result,
-        Err(error) => {
-            error!("{}", error);
-            return error.as_jint();
+/// Reporting the failure of a check of the bytecode, which has no call stack.
+/// A failure to report is only logged, the error code is returned anyway.
+#[cfg(feature = "jvm")]
+fn report_failure(env: &JNIEnv, callback: &JObject, error: &Error) {
+    let reporter = match (env.get_java_vm(), env.new_global_ref(callback)) {
+        (Ok(jvm), Ok(callback)) => Jvm::new(jvm, callback),
+        (Err(_), _) => {
+            error!("{}", JvmError::GetJavaVM);
+            return;
+        }
+        (_, Err(_)) => {
+            error!("{}", JvmError::NewGlobalRef);
+            return;
         }
     };
 
-    match result[0] {
-        Value::I32(value) => value as jint,
-        _ => 0 as jint,
+    if let Err(error) = reporter.execution_failed(&ErrorReport::new(error, vec![])) {
+        error!("{}", error);
     }
 }
 
-/// External Java function to validate bytecode contract.
+/// External Java function to check bytecode contract by the linter
+/// with the limits of the serialized [`VmConfig`].
+/// Returns the error code as big-endian `i32`, followed by
+/// the serialized [`LintReport`](crate::exec::lint::LintReport) if the check succeeded.
+/// A failure is also reported to the callback with `executionFailed`.
 #[cfg(feature = "jvm")]
 #[no_mangle]
-pub extern "system" fn Java_com_wavesenterprise_wasm_core_WASMExecutor_validateBytecode<'local>(
+pub extern "system" fn Java_com_wavesenterprise_wasm_core_WASMExecutor_lintBytecode<'local>(
     env: JNIEnv<'local>,
     _class: JClass<'local>,
     bytecode: JByteArray<'local>,
-) -> jint {
-    let bytecode = match env.convert_byte_array(bytecode) {
-        Ok(bytes) => bytes,
+    config: JByteArray<'local>,
+    callback: JObject<'local>,
+) -> JByteArray<'local> {
+    let mut report = vec![];
+
+    let code = catch_panic(None, || {
+        let result = env
+            .convert_byte_array(bytecode)
+            .map_err(|_| Error::Jvm(JvmError::ByteArrayConversion))
+            .and_then(|bytecode| {
+                let config = env
+                    .convert_byte_array(config)
+                    .map_err(|_| Error::Jvm(JvmError::ByteArrayConversion))
+                    .and_then(|bytes| VmConfig::deserialize(&bytes))?;
+
+                lint::lint(&bytecode, &config.lint)
+            });
+
+        match result {
+            Ok(result) => {
+                report = result.as_bytes();
+                0
+            }
+            Err(error) => {
+                error!("{}", error);
+                report_failure(&env, &callback, &error);
+                error.as_jint()
+            }
+        }
+    });
+
+    let mut bytes = code.to_be_bytes().to_vec();
+    if code == 0 {
+        bytes.extend(report);
+    }
+
+    match env.byte_array_from_slice(&bytes) {
+        Ok(array) => array,
         Err(_) => {
-            error!("{}", JvmError::ByteArrayConversion);
-            return JvmError::ByteArrayConversion.as_jint();
+            error!("{}", JvmError::NewByteArray);
+            JByteArray::default()
         }
-    };
+    }
+}
+
+/// Calling the function with a panic converted to the error code,
+/// since a panic unwinding across the JNI boundary aborts the JVM.
+#[cfg(feature = "jvm")]
+pub(crate) fn catch_panic<F: FnOnce() -> jint>(contract_id: Option<&[u8]>, func: F) -> jint {
+    match panic::catch_unwind(AssertUnwindSafe(func)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = match payload.downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => match payload.downcast_ref::<String>() {
+                    Some(message) => message.clone(),
+                    None => "Unknown panic payload".to_string(),
+                },
+            };
+
+            let error = ExecutableError::Panic(message);
+            match contract_id {
+                Some(contract_id) => error!("{} Contract: {}", error, contract_id.to_base58()),
+                None => error!("{}", error),
+            }
 
-    match Executable::validate_bytecode(&bytecode) {
-        Ok(_) => 0,
-        Err(error) => {
-            error!("{}", error);
             error.as_jint()
         }
     }
 }
 
-#[cfg(feature = "jvm")]
-fn modules() -> Vec<modules::Module> {
+/// Fuel costs of host functions.
+/// Calls are charged per byte of the data passed to them.
+#[cfg(feature = "vm")]
+pub fn cost_schedule() -> CostSchedule {
+    let mut schedule = CostSchedule::new(Cost::new(1, 0));
+
+    for (module, name, _) in signatures() {
+        let cost = match name.as_str() {
+            "fast_hash" | "secure_hash" | "blake2b256" | "keccak256" | "sha256" | "sig_verify"
+            | "contains_key" | "get_storage_int" | "get_storage_bool" | "get_storage_binary"
+            | "get_storage_string" | "get_storage" | "get_storage_uint" | "get_storage_int128"
+            | "get_storage_int256" | "get_storage_list" | "get_storage_map" | "get_balance" => {
+                Cost::new(10, 1)
+            }
+            "set_storage_int" | "set_storage_bool" | "set_storage_binary"
+            | "set_storage_string" | "set_storage_uint" | "set_storage_int128"
+            | "set_storage_int256" | "set_storage_list" | "set_storage_map"
+            | "set_storage_batch" | "delete_storage" | "transfer" | "issue" | "burn"
+            | "reissue" | "lease_address" | "lease_alias" | "cancel_lease" => Cost::new(20, 1),
+            "storage_keys" => Cost::new(10, 1).with_per_entry(5),
+            "call_contract" | "call_contract_params" => Cost::new(50, 1),
+            "call_arg_binary" | "call_arg_string" | "call_arg_int128" | "call_arg_int256"
+            | "call_arg_list" | "call_arg_map" | "call_payment" | "parse_int" | "parse_bool"
+            | "binary_equals" | "string_equals" | "join" | "contains" | "index_of"
+            | "last_index_of" | "base_58" | "to_base_58_string" | "to_le_bytes" | "require"
+            | "set_return" | "block" | "tx" => Cost::new(1, 1),
+            _ => continue,
+        };
+
+        schedule.set(&module, &name, cost);
+    }
+
+    schedule
+}
+
+/// All host functions available to contracts.
+#[cfg(feature = "vm")]
+pub fn modules() -> Vec<modules::Module> {
     let mut vec = vec![];
     vec.extend(v0::modules::modules());
     vec.extend(v1::modules::modules());
+    vec.extend(v2::modules::modules());
+    vec.extend(v3::modules::modules());
+    vec.extend(v4::modules::modules());
+    vec
+}
+
+/// Signatures of all host functions available to contracts.
+#[cfg(feature = "vm")]
+pub fn signatures() -> Vec<modules::Signature> {
+    let mut vec = vec![];
+    vec.extend(v0::modules::signatures());
+    vec.extend(v1::modules::signatures());
+    vec.extend(v2::modules::signatures());
+    vec.extend(v3::modules::signatures());
+    vec.extend(v4::modules::signatures());
     vec
 }
@@ -1,10 +1,17 @@
 pub mod v0;
 pub mod v1;
+pub mod v2;
+pub mod v3;
+pub mod v4;
 
 #[cfg(not(feature = "bindings"))]
 use crate::runtime::Runtime;
 #[cfg(not(feature = "bindings"))]
-use wasmi::{Func, Store};
+use wasmi::{Func, FuncType, Store};
 
 #[cfg(not(feature = "bindings"))]
 pub type Module = fn(&mut Store<Runtime>) -> (String, String, Func);
+
+/// Signature of a host function as `(module, name, type)`.
+#[cfg(not(feature = "bindings"))]
+pub type Signature = (String, String, FuncType);
@@ -3,6 +3,7 @@ use wevm_proc_macro::module;
 #[module(env0)]
 mod test {
     // Asset
+    #[cost(bytes = "length_asset_id, length_address")]
     fn get_balance(
         offset_asset_id: *const u8,
         length_asset_id: usize,
@@ -22,6 +23,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_asset_id, length_recipient")]
     fn transfer(
         offset_asset_id: *const u8,
         length_asset_id: usize,
@@ -43,6 +45,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_name, length_description")]
     fn issue(
         offset_name: *const u8,
         length_name: usize,
@@ -66,10 +69,12 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_asset_id")]
     fn burn(offset_asset_id: *const u8, length_asset_id: usize, amount: i64) -> i32 {
         |caller: Caller<Runtime>| env::asset::burn(offset_asset_id, length_asset_id, amount, caller)
     }
 
+    #[cost(bytes = "length_asset_id")]
     fn reissue(
         offset_asset_id: *const u8,
         length_asset_id: usize,
@@ -109,24 +114,28 @@ mod test {
         |caller: Caller<Runtime>| env::call_contract::call_arg_bool(value, caller)
     }
 
+    #[cost(bytes = "length_value")]
     fn call_arg_binary(offset_value: *const u8, length_value: usize) -> i32 {
         |caller: Caller<Runtime>| {
             env::call_contract::call_arg_binary(offset_value, length_value, caller)
         }
     }
 
+    #[cost(bytes = "length_value")]
     fn call_arg_string(offset_value: *const u8, length_value: usize) -> i32 {
         |caller: Caller<Runtime>| {
             env::call_contract::call_arg_string(offset_value, length_value, caller)
         }
     }
 
+    #[cost(bytes = "length_asset_id")]
     fn call_payment(offset_asset_id: *const u8, length_asset_id: usize, amount: i64) -> i32 {
         |caller: Caller<Runtime>| {
             env::call_contract::call_payment(offset_asset_id, length_asset_id, amount, caller)
         }
     }
 
+    #[cost(bytes = "length_contract_id, length_func_name")]
     fn call_contract(
         offset_contract_id: *const u8,
         length_contract_id: usize,
@@ -146,6 +155,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_contract_id, length_func_name, length_params")]
     fn call_contract_params(
         offset_contract_id: *const u8,
         length_contract_id: usize,
@@ -168,10 +178,12 @@ mod test {
     }
 
     // Converts
+    #[cost(bytes = "length")]
     fn parse_int(offset: *const u8, length: usize) -> (i32, i64) {
         |caller: Caller<Runtime>| env::converts::parse_int(offset, length, caller)
     }
 
+    #[cost(bytes = "length_string")]
     fn parse_bool(offset_string: *const u8, length_string: usize) -> (i32, bool) {
         |caller: Caller<Runtime>| env::converts::parse_bool(offset_string, length_string, caller)
     }
@@ -180,6 +192,7 @@ mod test {
         |caller: Caller<Runtime>| env::converts::to_bytes(value, caller)
     }
 
+    #[cost(bytes = "length")]
     fn to_int(offset: *const u8, length: usize) -> (i32, i64) {
         |caller: Caller<Runtime>| env::converts::to_int(offset, length, caller)
     }
@@ -193,26 +206,32 @@ mod test {
     }
 
     // Crypto
+    #[cost(bytes = "length_bytes")]
     fn fast_hash(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| env::crypto::fast_hash(offset_bytes, length_bytes, caller)
     }
 
+    #[cost(bytes = "length_bytes")]
     fn secure_hash(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| env::crypto::secure_hash(offset_bytes, length_bytes, caller)
     }
 
+    #[cost(bytes = "length_bytes")]
     fn blake2b256(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| env::crypto::blake2b256(offset_bytes, length_bytes, caller)
     }
 
+    #[cost(bytes = "length_bytes")]
     fn keccak256(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| env::crypto::keccak256(offset_bytes, length_bytes, caller)
     }
 
+    #[cost(bytes = "length_bytes")]
     fn sha256(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| env::crypto::sha256(offset_bytes, length_bytes, caller)
     }
 
+    #[cost(bytes = "length_message, length_signature, length_public_key")]
     fn sig_verify(
         offset_message: *const u8,
         length_message: usize,
@@ -235,6 +254,7 @@ mod test {
     }
 
     // Lease
+    #[cost(bytes = "length_address")]
     fn lease_address(
         offset_address: *const u8,
         length_address: usize,
@@ -245,6 +265,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_alias")]
     fn lease_alias(
         offset_alias: *const u8,
         length_alias: usize,
@@ -253,11 +274,13 @@ mod test {
         |caller: Caller<Runtime>| env::lease::lease(offset_alias, length_alias, 2, amount, caller)
     }
 
+    #[cost(bytes = "length_lease_id")]
     fn cancel_lease(offset_lease_id: *const u8, length_lease_id: usize) -> i32 {
         |caller: Caller<Runtime>| env::lease::cancel_lease(offset_lease_id, length_lease_id, caller)
     }
 
     // Memory
+    #[cost(bytes = "length_left, length_right")]
     fn binary_equals(
         offset_left: *const u8,
         length_left: usize,
@@ -269,6 +292,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_left, length_right")]
     fn string_equals(
         offset_left: *const u8,
         length_left: usize,
@@ -280,6 +304,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_left, length_right")]
     fn join(
         offset_left: *const u8,
         length_left: usize,
@@ -291,6 +316,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_bytes, length_subbytes")]
     fn contains(
         offset_bytes: *const u8,
         length_bytes: usize,
@@ -308,14 +334,17 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_bytes")]
     fn drop(offset_bytes: *const u8, length_bytes: usize, n: i64) -> (i32, *const u8, usize) {
         |_caller: Caller<Runtime>| env::memory::drop(offset_bytes, length_bytes, n)
     }
 
+    #[cost(bytes = "length_bytes")]
     fn drop_right(offset_bytes: *const u8, length_bytes: usize, n: i64) -> (i32, *const u8, usize) {
         |_caller: Caller<Runtime>| env::memory::drop_right(offset_bytes, length_bytes, n)
     }
 
+    #[cost(bytes = "length_string, length_substring")]
     fn index_of(
         offset_string: *const u8,
         length_string: usize,
@@ -334,6 +363,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_string, length_substring")]
     fn last_index_of(
         offset_string: *const u8,
         length_string: usize,
@@ -352,15 +382,18 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_bytes")]
     fn take(offset_bytes: *const u8, length_bytes: usize, n: i64) -> (i32, *const u8, usize) {
         |_caller: Caller<Runtime>| env::memory::take(offset_bytes, length_bytes, n)
     }
 
+    #[cost(bytes = "length_bytes")]
     fn take_right(offset_bytes: *const u8, length_bytes: usize, n: i64) -> (i32, *const u8, usize) {
         |_caller: Caller<Runtime>| env::memory::take_right(offset_bytes, length_bytes, n)
     }
 
     // Storage
+    #[cost(bytes = "length_address, length_key")]
     fn contains_key(
         offset_address: *const u8,
         length_address: usize,
@@ -378,6 +411,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_address, length_key")]
     fn get_storage_int(
         offset_address: *const u8,
         length_address: usize,
@@ -395,6 +429,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_address, length_key")]
     fn get_storage_bool(
         offset_address: *const u8,
         length_address: usize,
@@ -412,6 +447,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_address, length_key")]
     fn get_storage_binary(
         offset_address: *const u8,
         length_address: usize,
@@ -429,6 +465,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_address, length_key")]
     fn get_storage_string(
         offset_address: *const u8,
         length_address: usize,
@@ -446,18 +483,21 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_key")]
     fn set_storage_int(offset_key: *const u8, length_key: usize, value: i64) -> i32 {
         |caller: Caller<Runtime>| {
             env::storage::set_storage_int(offset_key, length_key, value, caller)
         }
     }
 
+    #[cost(bytes = "length_key")]
     fn set_storage_bool(offset_key: *const u8, length_key: usize, value: bool) -> i32 {
         |caller: Caller<Runtime>| {
             env::storage::set_storage_bool(offset_key, length_key, value, caller)
         }
     }
 
+    #[cost(bytes = "length_key, length_value")]
     fn set_storage_binary(
         offset_key: *const u8,
         length_key: usize,
@@ -475,6 +515,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_key, length_value")]
     fn set_storage_string(
         offset_key: *const u8,
         length_key: usize,
@@ -519,14 +560,17 @@ mod test {
     }
 
     // Utils
+    #[cost(bytes = "length_bytes")]
     fn base_58(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| env::utils::base58(offset_bytes, length_bytes, caller)
     }
 
+    #[cost(bytes = "length_bytes")]
     fn to_base_58_string(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| env::utils::to_base58_string(offset_bytes, length_bytes, caller)
     }
 
+    #[cost(bytes = "length_bytes")]
     fn to_le_bytes(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| env::utils::to_le_bytes(offset_bytes, length_bytes, caller)
     }
@@ -535,6 +579,7 @@ mod test {
         |caller: Caller<Runtime>| env::utils::caller(caller)
     }
 
+    #[cost(bytes = "length_message")]
     fn require(offset_message: *const u8, length_message: usize) -> i32 {
         |caller: Caller<Runtime>| env::utils::require(offset_message, length_message, caller)
     }
@@ -3,6 +3,7 @@ use wevm_proc_macro::module;
 #[module(env1)]
 mod test {
     // Asset
+    #[cost(bytes = "length_asset_id, length_holder")]
     fn get_balance(
         offset_asset_id: *const u8,
         length_asset_id: usize,
@@ -24,6 +25,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_asset_id, length_recipient")]
     fn transfer(
         offset_asset_id: *const u8,
         length_asset_id: usize,
@@ -47,6 +49,7 @@ mod test {
         }
     }
 
+    #[cost(bytes = "length_name, length_description")]
     fn issue(
         offset_name: *const u8,
         length_name: usize,
@@ -71,6 +74,7 @@ mod test {
     }
 
     // Block
+    #[cost(bytes = "length_field")]
     fn block(offset_field: *const u8, length_field: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| {
             env::block::block(env::Field::Binary(offset_field, length_field), caller)
@@ -90,6 +94,7 @@ mod test {
         |caller: Caller<Runtime>| env::tx::get_payment_amount(number, caller)
     }
 
+    #[cost(bytes = "length_field")]
     fn tx(offset_field: *const u8, length_field: usize) -> (i32, *const u8, usize) {
         |caller: Caller<Runtime>| {
             env::tx::tx(env::Field::Binary(offset_field, length_field), caller)
@@ -0,0 +1,52 @@
+use wevm_proc_macro::module;
+
+#[module(env2)]
+mod test {
+    // Call contract
+    #[cost(bytes = "length_contract_id, length_func_name")]
+    fn call_contract(
+        offset_contract_id: *const u8,
+        length_contract_id: usize,
+        offset_func_name: *const u8,
+        length_func_name: usize,
+    ) -> (i32, *const u8, usize) {
+        |caller: Caller<Runtime>| {
+            env::call_contract::call_contract_return(
+                offset_contract_id,
+                length_contract_id,
+                offset_func_name,
+                length_func_name,
+                None,
+                None,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_contract_id, length_func_name, length_params")]
+    fn call_contract_params(
+        offset_contract_id: *const u8,
+        length_contract_id: usize,
+        offset_func_name: *const u8,
+        length_func_name: usize,
+        offset_params: *const u8,
+        length_params: usize,
+    ) -> (i32, *const u8, usize) {
+        |caller: Caller<Runtime>| {
+            env::call_contract::call_contract_return(
+                offset_contract_id,
+                length_contract_id,
+                offset_func_name,
+                length_func_name,
+                Some(offset_params),
+                Some(length_params),
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_value")]
+    fn set_return(offset_value: *const u8, length_value: usize) -> i32 {
+        |caller: Caller<Runtime>| env::call_contract::set_return(offset_value, length_value, caller)
+    }
+}
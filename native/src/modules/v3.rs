@@ -0,0 +1,62 @@
+use wevm_proc_macro::module;
+
+#[module(env3)]
+mod test {
+    // Storage
+    #[cost(bytes = "length_address, length_key")]
+    fn get_storage(
+        offset_address: *const u8,
+        length_address: usize,
+        offset_key: *const u8,
+        length_key: usize,
+    ) -> (i32, i32, *const u8, usize) {
+        |caller: Caller<Runtime>| {
+            env::storage::get_storage(
+                offset_address,
+                length_address,
+                offset_key,
+                length_key,
+                caller,
+            )
+        }
+    }
+
+    #[cost(
+        bytes = "length_address, length_prefix, length_cursor",
+        entries = "limit"
+    )]
+    fn storage_keys(
+        offset_address: *const u8,
+        length_address: usize,
+        offset_prefix: *const u8,
+        length_prefix: usize,
+        offset_cursor: *const u8,
+        length_cursor: usize,
+        limit: u32,
+    ) -> (i32, *const u8, usize) {
+        |caller: Caller<Runtime>| {
+            env::storage::storage_keys(
+                offset_address,
+                length_address,
+                offset_prefix,
+                length_prefix,
+                offset_cursor,
+                length_cursor,
+                limit,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_values")]
+    fn set_storage_batch(offset_values: *const u8, length_values: usize) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::storage::set_storage_batch(offset_values, length_values, caller)
+        }
+    }
+
+    #[cost(bytes = "length_key")]
+    fn delete_storage(offset_key: *const u8, length_key: usize) -> i32 {
+        |caller: Caller<Runtime>| env::storage::delete_storage(offset_key, length_key, caller)
+    }
+}
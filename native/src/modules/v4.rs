@@ -0,0 +1,235 @@
+use wevm_proc_macro::module;
+
+#[module(env4)]
+mod test {
+    // Call contract
+    fn call_arg_uint(value: i64) {
+        |caller: Caller<Runtime>| env::call_contract::call_arg_uint(value, caller)
+    }
+
+    #[cost(bytes = "length_value")]
+    fn call_arg_int128(offset_value: *const u8, length_value: usize) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::call_contract::call_arg_tagged(
+                data_entry::TAG_INTEGER128,
+                offset_value,
+                length_value,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_value")]
+    fn call_arg_int256(offset_value: *const u8, length_value: usize) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::call_contract::call_arg_tagged(
+                data_entry::TAG_INTEGER256,
+                offset_value,
+                length_value,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_value")]
+    fn call_arg_list(offset_value: *const u8, length_value: usize) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::call_contract::call_arg_tagged(
+                data_entry::TAG_LIST,
+                offset_value,
+                length_value,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_value")]
+    fn call_arg_map(offset_value: *const u8, length_value: usize) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::call_contract::call_arg_tagged(
+                data_entry::TAG_MAP,
+                offset_value,
+                length_value,
+                caller,
+            )
+        }
+    }
+
+    // Storage
+    #[cost(bytes = "length_address, length_key")]
+    fn get_storage_uint(
+        offset_address: *const u8,
+        length_address: usize,
+        offset_key: *const u8,
+        length_key: usize,
+    ) -> (i32, i64) {
+        |caller: Caller<Runtime>| {
+            env::storage::get_storage_uint(
+                offset_address,
+                length_address,
+                offset_key,
+                length_key,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_address, length_key")]
+    fn get_storage_int128(
+        offset_address: *const u8,
+        length_address: usize,
+        offset_key: *const u8,
+        length_key: usize,
+    ) -> (i32, *const u8, usize) {
+        |caller: Caller<Runtime>| {
+            env::storage::get_storage_tagged(
+                data_entry::TAG_INTEGER128,
+                offset_address,
+                length_address,
+                offset_key,
+                length_key,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_address, length_key")]
+    fn get_storage_int256(
+        offset_address: *const u8,
+        length_address: usize,
+        offset_key: *const u8,
+        length_key: usize,
+    ) -> (i32, *const u8, usize) {
+        |caller: Caller<Runtime>| {
+            env::storage::get_storage_tagged(
+                data_entry::TAG_INTEGER256,
+                offset_address,
+                length_address,
+                offset_key,
+                length_key,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_address, length_key")]
+    fn get_storage_list(
+        offset_address: *const u8,
+        length_address: usize,
+        offset_key: *const u8,
+        length_key: usize,
+    ) -> (i32, *const u8, usize) {
+        |caller: Caller<Runtime>| {
+            env::storage::get_storage_tagged(
+                data_entry::TAG_LIST,
+                offset_address,
+                length_address,
+                offset_key,
+                length_key,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_address, length_key")]
+    fn get_storage_map(
+        offset_address: *const u8,
+        length_address: usize,
+        offset_key: *const u8,
+        length_key: usize,
+    ) -> (i32, *const u8, usize) {
+        |caller: Caller<Runtime>| {
+            env::storage::get_storage_tagged(
+                data_entry::TAG_MAP,
+                offset_address,
+                length_address,
+                offset_key,
+                length_key,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_key")]
+    fn set_storage_uint(offset_key: *const u8, length_key: usize, value: i64) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::storage::set_storage_uint(offset_key, length_key, value, caller)
+        }
+    }
+
+    #[cost(bytes = "length_key, length_value")]
+    fn set_storage_int128(
+        offset_key: *const u8,
+        length_key: usize,
+        offset_value: *const u8,
+        length_value: usize,
+    ) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::storage::set_storage_tagged(
+                data_entry::TAG_INTEGER128,
+                offset_key,
+                length_key,
+                offset_value,
+                length_value,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_key, length_value")]
+    fn set_storage_int256(
+        offset_key: *const u8,
+        length_key: usize,
+        offset_value: *const u8,
+        length_value: usize,
+    ) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::storage::set_storage_tagged(
+                data_entry::TAG_INTEGER256,
+                offset_key,
+                length_key,
+                offset_value,
+                length_value,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_key, length_value")]
+    fn set_storage_list(
+        offset_key: *const u8,
+        length_key: usize,
+        offset_value: *const u8,
+        length_value: usize,
+    ) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::storage::set_storage_tagged(
+                data_entry::TAG_LIST,
+                offset_key,
+                length_key,
+                offset_value,
+                length_value,
+                caller,
+            )
+        }
+    }
+
+    #[cost(bytes = "length_key, length_value")]
+    fn set_storage_map(
+        offset_key: *const u8,
+        length_key: usize,
+        offset_value: *const u8,
+        length_value: usize,
+    ) -> i32 {
+        |caller: Caller<Runtime>| {
+            env::storage::set_storage_tagged(
+                data_entry::TAG_MAP,
+                offset_key,
+                length_key,
+                offset_value,
+                length_value,
+                caller,
+            )
+        }
+    }
+}
@@ -1,3 +1,5 @@
+pub mod ledger;
+
 use crate::error::Result;
 
 /// Interface of interaction with the node.
@@ -45,6 +47,17 @@ pub trait Node {
     fn contains_key(&self, address: &[u8], key: &[u8]) -> Result<bool>;
     fn get_storage(&self, address: &[u8], key: &[u8]) -> Result<Vec<u8>>;
     fn set_storage(&self, contract_id: &[u8], value: &[u8]) -> Result<()>;
+    fn set_storage_batch(&self, contract_id: &[u8], values: &[u8]) -> Result<()>;
+    fn delete_storage(&self, contract_id: &[u8], key: &[u8]) -> Result<()>;
+    /// Keys of the storage starting with the prefix, in ascending byte order
+    /// after the cursor key (from the beginning if the cursor is empty), at most `limit`.
+    fn storage_keys(
+        &self,
+        address: &[u8],
+        prefix: &[u8],
+        cursor: &[u8],
+        limit: u32,
+    ) -> Result<Vec<Vec<u8>>>;
     // Tx
     fn get_tx_payments(&self, payment_id: &[u8]) -> Result<i64>;
     fn get_tx_payment_asset_id(&self, payment_id: &[u8], number: i64) -> Result<Vec<u8>>;
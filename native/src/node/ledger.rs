@@ -0,0 +1,628 @@
+use crate::{
+    error::{Error, Result, RuntimeError},
+    node::Node,
+    runtime::{data_entry::DataEntry, utils},
+};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use sha3::Keccak256;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+/// Length of the asset identifier in serialized payments.
+const ASSET_ID_LENGTH: usize = 32;
+
+/// Length of the nonce at the end of the payment identifier.
+const NONCE_LENGTH: usize = 8;
+
+macro_rules! reject {
+    ($($arg:tt)*) => {
+        Err(Error::Runtime(RuntimeError::NodeRejected(format!($($arg)*))))
+    };
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Lease {
+    pub contract_id: Vec<u8>,
+    pub recipient: Vec<u8>,
+    pub amount: i64,
+}
+
+/// Signature verifier taking the message, the signature and the public key.
+pub type SigVerifier = Rc<dyn Fn(&[u8], &[u8], &[u8]) -> bool>;
+
+#[derive(Default)]
+struct State {
+    chain_id: i8,
+    nonce: u64,
+    error_message: Vec<u8>,
+    bytecodes: HashMap<Vec<u8>, Vec<u8>>,
+    // (asset_id, holder) -> amount
+    balances: HashMap<(Vec<u8>, Vec<u8>), i64>,
+    // (address, key) -> serialized DataEntry
+    storage: HashMap<(Vec<u8>, Vec<u8>), Vec<u8>>,
+    leases: HashMap<Vec<u8>, Lease>,
+    // payment_id -> [(asset_id, amount)]
+    payments: HashMap<Vec<u8>, Vec<(Vec<u8>, i64)>>,
+    block: HashMap<Vec<u8>, Vec<u8>>,
+    tx: HashMap<Vec<u8>, Vec<u8>>,
+    // (message, signature, public_key) accepted without a verifier
+    signatures: HashSet<(Vec<u8>, Vec<u8>, Vec<u8>)>,
+    sig_verifier: Option<SigVerifier>,
+}
+
+/// In-memory implementation of the node.
+///
+/// Keeps balances, storage, bytecode of contracts, leases, payments and
+/// block/tx fields, which makes it possible to run contracts without a JVM.
+/// Clones share the same state, so a clone can be handed to the VM
+/// and the other one used to prepare and inspect the ledger.
+#[derive(Clone, Default)]
+pub struct Ledger {
+    state: Rc<RefCell<State>>,
+}
+
+impl Ledger {
+    pub fn new(chain_id: i8) -> Self {
+        let ledger = Self::default();
+        ledger.state.borrow_mut().chain_id = chain_id;
+        ledger
+    }
+
+    /// Registering the bytecode of a contract available for `call_contract`.
+    pub fn set_bytecode(&self, contract_id: &[u8], bytecode: Vec<u8>) {
+        self.state
+            .borrow_mut()
+            .bytecodes
+            .insert(contract_id.to_vec(), bytecode);
+    }
+
+    /// Getting the balance of the holder without the `AssetHolder` prefix.
+    pub fn balance(&self, asset_id: &[u8], holder: &[u8]) -> i64 {
+        let state = self.state.borrow();
+        *state
+            .balances
+            .get(&(asset_id.to_vec(), holder.to_vec()))
+            .unwrap_or(&0)
+    }
+
+    /// Setting the balance of the holder without the `AssetHolder` prefix.
+    pub fn set_balance(&self, asset_id: &[u8], holder: &[u8], amount: i64) {
+        self.state
+            .borrow_mut()
+            .balances
+            .insert((asset_id.to_vec(), holder.to_vec()), amount);
+    }
+
+    /// Getting the value stored by the contract under the key.
+    pub fn storage(&self, address: &[u8], key: &[u8]) -> Option<DataEntry> {
+        let state = self.state.borrow();
+        let bytes = state.storage.get(&(address.to_vec(), key.to_vec()))?;
+        DataEntry::deserialize(bytes).ok()
+    }
+
+    /// Storing the value under the key on behalf of the contract.
+    pub fn set_storage_entry(&self, address: &[u8], key: &[u8], value: DataEntry) {
+        self.state
+            .borrow_mut()
+            .storage
+            .insert((address.to_vec(), key.to_vec()), value.serialize(Some(key)));
+    }
+
+    pub fn lease_info(&self, lease_id: &[u8]) -> Option<Lease> {
+        self.state.borrow().leases.get(lease_id).cloned()
+    }
+
+    /// Attaching a payment to the transaction that calls the contract.
+    pub fn add_tx_payment(&self, payment_id: &[u8], asset_id: &[u8], amount: i64) {
+        self.state
+            .borrow_mut()
+            .payments
+            .entry(payment_id.to_vec())
+            .or_default()
+            .push((asset_id.to_vec(), amount));
+    }
+
+    pub fn set_block_field(&self, field: &str, value: Vec<u8>) {
+        self.state
+            .borrow_mut()
+            .block
+            .insert(field.as_bytes().to_vec(), value);
+    }
+
+    pub fn set_tx_field(&self, field: &str, value: Vec<u8>) {
+        self.state
+            .borrow_mut()
+            .tx
+            .insert(field.as_bytes().to_vec(), value);
+    }
+
+    /// Accepting the signature of the message by `sig_verify`.
+    pub fn allow_signature(&self, message: &[u8], signature: &[u8], public_key: &[u8]) {
+        self.state.borrow_mut().signatures.insert((
+            message.to_vec(),
+            signature.to_vec(),
+            public_key.to_vec(),
+        ));
+    }
+
+    /// Setting the verifier used by `sig_verify` instead of the allowed signatures,
+    /// e.g. the same Curve25519 verification as the node.
+    pub fn set_sig_verifier(&self, verifier: SigVerifier) {
+        self.state.borrow_mut().sig_verifier = Some(verifier);
+    }
+
+    /// The last message passed to `require`.
+    pub fn error_message(&self) -> Vec<u8> {
+        self.state.borrow().error_message.clone()
+    }
+
+    /// Strips the `AssetHolder` prefix to get the address or the contract id.
+    fn holder(asset_holder: &[u8]) -> Result<Vec<u8>> {
+        match asset_holder.first() {
+            Some(0) | Some(1) => Ok(asset_holder[1..].to_vec()),
+            _ => Err(Error::Runtime(RuntimeError::AssetHolderTypeNotFound)),
+        }
+    }
+
+    fn move_funds(
+        &self,
+        asset_id: &[u8],
+        sender: &[u8],
+        recipient: &[u8],
+        amount: i64,
+    ) -> Result<()> {
+        Self::check_amount(amount)?;
+
+        let balance = self.balance(asset_id, sender);
+        if balance < amount {
+            return reject!("Insufficient funds: {} < {}", balance, amount);
+        }
+
+        // The recipient is checked first, so a failure leaves both balances unchanged
+        let received = self.add(asset_id, recipient, amount)?;
+        self.set_balance(asset_id, sender, balance - amount);
+        self.set_balance(asset_id, recipient, received);
+
+        Ok(())
+    }
+
+    fn check_amount(amount: i64) -> Result<()> {
+        match amount < 0 {
+            true => reject!("Negative amount: {}", amount),
+            false => Ok(()),
+        }
+    }
+
+    /// Getting the balance of the holder increased by the amount, without setting it.
+    fn add(&self, asset_id: &[u8], holder: &[u8], amount: i64) -> Result<i64> {
+        let balance = self.balance(asset_id, holder);
+        match balance.checked_add(amount) {
+            Some(balance) => Ok(balance),
+            None => reject!("Balance overflow: {} + {}", balance, amount),
+        }
+    }
+
+    fn next_id(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let nonce = {
+            let mut state = self.state.borrow_mut();
+            state.nonce += 1;
+            state.nonce
+        };
+
+        let mut input = bytes.to_vec();
+        input.extend_from_slice(&nonce.to_be_bytes());
+        self.secure_hash(&input)
+    }
+}
+
+impl Node for Ledger {
+    fn get_chain_id(&self) -> Result<i8> {
+        Ok(self.state.borrow().chain_id)
+    }
+
+    fn require(&self, message: &[u8]) -> Result<()> {
+        self.state.borrow_mut().error_message = message.to_vec();
+        Ok(())
+    }
+
+    fn get_bytecode(&self, contract_id: &[u8]) -> Result<Vec<u8>> {
+        match self.state.borrow().bytecodes.get(contract_id) {
+            Some(bytecode) => Ok(bytecode.clone()),
+            None => reject!("Contract not found"),
+        }
+    }
+
+    fn add_payments(&self, contract_id: &[u8], payment_id: &[u8], payments: &[u8]) -> Result<()> {
+        if payment_id.len() < NONCE_LENGTH {
+            return reject!("Invalid payment id");
+        }
+        let callable_contract_id = &payment_id[..payment_id.len() - NONCE_LENGTH];
+
+        let mut offset = 0;
+        let count = utils::get_u16(payments, &mut offset)?;
+
+        for _ in 0..count {
+            let asset_id = match utils::get_u8(payments, &mut offset)? {
+                0 => vec![],
+                1 => utils::get_bytes(payments, &mut offset, ASSET_ID_LENGTH)?,
+                _ => return reject!("Invalid payments"),
+            };
+            let amount = utils::get_u64(payments, &mut offset)? as i64;
+
+            self.move_funds(&asset_id, contract_id, callable_contract_id, amount)?;
+            self.add_tx_payment(payment_id, &asset_id, amount);
+        }
+
+        Ok(())
+    }
+
+    // Asset
+    fn get_balance(&self, asset_id: &[u8], address: &[u8]) -> Result<i64> {
+        Ok(self.balance(asset_id, &Self::holder(address)?))
+    }
+
+    fn transfer(
+        &self,
+        contract_id: &[u8],
+        asset_id: &[u8],
+        recipient: &[u8],
+        amount: i64,
+    ) -> Result<()> {
+        self.move_funds(asset_id, contract_id, &Self::holder(recipient)?, amount)
+    }
+
+    fn issue(
+        &self,
+        contract_id: &[u8],
+        name: &[u8],
+        description: &[u8],
+        quantity: i64,
+        _decimals: i64,
+        _is_reissuable: bool,
+    ) -> Result<Vec<u8>> {
+        Self::check_amount(quantity)?;
+
+        let mut bytes = name.to_vec();
+        bytes.extend_from_slice(description);
+
+        let asset_id = self.next_id(&bytes)?;
+        self.set_balance(&asset_id, contract_id, quantity);
+
+        Ok(asset_id)
+    }
+
+    fn burn(&self, contract_id: &[u8], asset_id: &[u8], amount: i64) -> Result<()> {
+        Self::check_amount(amount)?;
+
+        let balance = self.balance(asset_id, contract_id);
+        match balance.checked_sub(amount) {
+            Some(rest) if rest >= 0 => self.set_balance(asset_id, contract_id, rest),
+            _ => return reject!("Insufficient funds: {} < {}", balance, amount),
+        }
+
+        Ok(())
+    }
+
+    fn reissue(
+        &self,
+        contract_id: &[u8],
+        asset_id: &[u8],
+        amount: i64,
+        _is_reissuable: bool,
+    ) -> Result<()> {
+        Self::check_amount(amount)?;
+
+        let balance = self.add(asset_id, contract_id, amount)?;
+        self.set_balance(asset_id, contract_id, balance);
+        Ok(())
+    }
+
+    // Block
+    fn block(&self, field: &[u8]) -> Result<Vec<u8>> {
+        match self.state.borrow().block.get(field) {
+            Some(value) => Ok(value.clone()),
+            None => reject!("Unknown block field"),
+        }
+    }
+
+    // Crypto
+    fn fast_hash(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut hasher: Blake2b<U32> = Blake2b::new();
+        hasher.update(bytes);
+        Ok(hasher.finalize().to_vec())
+    }
+
+    fn secure_hash(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.fast_hash(bytes)?);
+        Ok(hasher.finalize().to_vec())
+    }
+
+    fn sig_verify(&self, message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool> {
+        let state = self.state.borrow();
+
+        match &state.sig_verifier {
+            Some(verifier) => Ok(verifier(message, signature, public_key)),
+            None => Ok(state.signatures.contains(&(
+                message.to_vec(),
+                signature.to_vec(),
+                public_key.to_vec(),
+            ))),
+        }
+    }
+
+    // Lease
+    fn lease(&self, contract_id: &[u8], recipient: &[u8], amount: i64) -> Result<Vec<u8>> {
+        if recipient.first() != Some(&0) {
+            return reject!("Lease recipient must be an account");
+        }
+
+        let mut bytes = contract_id.to_vec();
+        bytes.extend_from_slice(recipient);
+        let lease_id = self.next_id(&bytes)?;
+
+        let lease = Lease {
+            contract_id: contract_id.to_vec(),
+            recipient: Self::holder(recipient)?,
+            amount,
+        };
+        self.state
+            .borrow_mut()
+            .leases
+            .insert(lease_id.clone(), lease);
+
+        Ok(lease_id)
+    }
+
+    fn cancel_lease(&self, contract_id: &[u8], lease_id: &[u8]) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+
+        match state.leases.get(lease_id) {
+            Some(lease) if lease.contract_id == contract_id => {
+                state.leases.remove(lease_id);
+                Ok(())
+            }
+            _ => reject!("Lease not found"),
+        }
+    }
+
+    // Storage
+    fn contains_key(&self, address: &[u8], key: &[u8]) -> Result<bool> {
+        let state = self.state.borrow();
+        Ok(state
+            .storage
+            .contains_key(&(address.to_vec(), key.to_vec())))
+    }
+
+    fn get_storage(&self, address: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+        let state = self.state.borrow();
+        Ok(state
+            .storage
+            .get(&(address.to_vec(), key.to_vec()))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn set_storage(&self, contract_id: &[u8], value: &[u8]) -> Result<()> {
+        let mut offset = 0;
+        let length = utils::get_u16(value, &mut offset)?;
+        let key = utils::get_bytes(value, &mut offset, length as usize)?;
+
+        self.state
+            .borrow_mut()
+            .storage
+            .insert((contract_id.to_vec(), key), value.to_vec());
+
+        Ok(())
+    }
+
+    fn set_storage_batch(&self, contract_id: &[u8], values: &[u8]) -> Result<()> {
+        for value in DataEntry::split_batch(values)? {
+            self.set_storage(contract_id, &value)?;
+        }
+
+        Ok(())
+    }
+
+    fn delete_storage(&self, contract_id: &[u8], key: &[u8]) -> Result<()> {
+        self.state
+            .borrow_mut()
+            .storage
+            .remove(&(contract_id.to_vec(), key.to_vec()));
+
+        Ok(())
+    }
+
+    fn storage_keys(
+        &self,
+        address: &[u8],
+        prefix: &[u8],
+        cursor: &[u8],
+        limit: u32,
+    ) -> Result<Vec<Vec<u8>>> {
+        let state = self.state.borrow();
+
+        let mut keys: Vec<Vec<u8>> = state
+            .storage
+            .keys()
+            .filter(|(id, key)| id == address && key.starts_with(prefix) && key.as_slice() > cursor)
+            .map(|(_, key)| key.clone())
+            .collect();
+        keys.sort();
+        keys.truncate(limit as usize);
+
+        Ok(keys)
+    }
+
+    // Tx
+    fn get_tx_payments(&self, payment_id: &[u8]) -> Result<i64> {
+        let state = self.state.borrow();
+        Ok(state.payments.get(payment_id).map_or(0, |item| item.len()) as i64)
+    }
+
+    fn get_tx_payment_asset_id(&self, payment_id: &[u8], number: i64) -> Result<Vec<u8>> {
+        let state = self.state.borrow();
+        match state
+            .payments
+            .get(payment_id)
+            .and_then(|item| item.get(number as usize))
+        {
+            Some((asset_id, _)) => Ok(asset_id.clone()),
+            None => reject!("Payment not found"),
+        }
+    }
+
+    fn get_tx_payment_amount(&self, payment_id: &[u8], number: i64) -> Result<i64> {
+        let state = self.state.borrow();
+        match state
+            .payments
+            .get(payment_id)
+            .and_then(|item| item.get(number as usize))
+        {
+            Some((_, amount)) => Ok(*amount),
+            None => reject!("Payment not found"),
+        }
+    }
+
+    fn tx(&self, field: &[u8]) -> Result<Vec<u8>> {
+        match self.state.borrow().tx.get(field) {
+            Some(value) => Ok(value.clone()),
+            None => reject!("Unknown tx field"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::payments::Payments;
+
+    #[test]
+    fn test_ledger_storage() {
+        let ledger = Ledger::new(b'V' as i8);
+        let value = DataEntry::Integer(42).serialize(Some(b"key"));
+
+        ledger
+            .set_storage(b"contract", &value)
+            .expect("Error set storage");
+
+        assert_eq!(ledger.contains_key(b"contract", b"key"), Ok(true));
+        assert_eq!(ledger.contains_key(b"other", b"key"), Ok(false));
+        assert_eq!(ledger.get_storage(b"contract", b"key"), Ok(value));
+        assert_eq!(
+            ledger.storage(b"contract", b"key"),
+            Some(DataEntry::Integer(42))
+        );
+    }
+
+    #[test]
+    fn test_ledger_payments() {
+        let ledger = Ledger::default();
+        let caller = [1u8; 32];
+        let callee = [2u8; 32];
+
+        ledger.set_balance(&[], &caller, 100);
+
+        let mut payments = Payments::new();
+        payments.push(&[], 40);
+
+        let mut payment_id = callee.to_vec();
+        payment_id.extend_from_slice(&1u64.to_be_bytes());
+
+        ledger
+            .add_payments(&caller, &payment_id, &payments.as_bytes())
+            .expect("Error add payments");
+
+        assert_eq!(ledger.balance(&[], &caller), 60);
+        assert_eq!(ledger.balance(&[], &callee), 40);
+        assert_eq!(ledger.get_tx_payments(&payment_id), Ok(1));
+        assert_eq!(ledger.get_tx_payment_amount(&payment_id, 0), Ok(40));
+
+        payments.reset();
+        payments.push(&[], 100);
+
+        assert!(ledger
+            .add_payments(&caller, &payment_id, &payments.as_bytes())
+            .is_err());
+    }
+
+    #[test]
+    fn test_ledger_negative_amounts() {
+        let ledger = Ledger::default();
+        let contract = [1u8; 32];
+        let mut recipient = vec![0u8];
+        recipient.extend_from_slice(&[2u8; 26]);
+
+        assert!(ledger.issue(&contract, b"name", b"", -1, 0, true).is_err());
+
+        let asset_id = ledger
+            .issue(&contract, b"name", b"", 100, 0, true)
+            .expect("Error issue");
+
+        assert!(ledger.burn(&contract, &asset_id, -10).is_err());
+        assert!(ledger.reissue(&contract, &asset_id, -10, true).is_err());
+        assert!(ledger
+            .transfer(&contract, &asset_id, &recipient, -10)
+            .is_err());
+        assert_eq!(ledger.balance(&asset_id, &contract), 100);
+
+        assert!(ledger.burn(&contract, &asset_id, 101).is_err());
+        assert_eq!(ledger.burn(&contract, &asset_id, 100), Ok(()));
+        assert_eq!(ledger.balance(&asset_id, &contract), 0);
+    }
+
+    #[test]
+    fn test_ledger_overflow() {
+        let ledger = Ledger::default();
+        let contract = [1u8; 32];
+        let holder = [2u8; 26];
+        let mut recipient = vec![0u8];
+        recipient.extend_from_slice(&holder);
+
+        let asset_id = ledger
+            .issue(&contract, b"name", b"", i64::MAX, 0, true)
+            .expect("Error issue");
+
+        assert!(ledger.reissue(&contract, &asset_id, 1, true).is_err());
+        assert_eq!(ledger.balance(&asset_id, &contract), i64::MAX);
+
+        ledger.set_balance(&asset_id, &holder, 1);
+        assert!(ledger
+            .transfer(&contract, &asset_id, &recipient, i64::MAX)
+            .is_err());
+        assert_eq!(ledger.balance(&asset_id, &contract), i64::MAX);
+        assert_eq!(ledger.balance(&asset_id, &holder), 1);
+
+        ledger.set_balance(&asset_id, &contract, -2);
+        assert!(ledger.burn(&contract, &asset_id, i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_ledger_sig_verify() {
+        let ledger = Ledger::default();
+
+        assert_eq!(
+            ledger.sig_verify(b"message", b"signature", b"key"),
+            Ok(false)
+        );
+
+        ledger.allow_signature(b"message", b"signature", b"key");
+        assert_eq!(
+            ledger.sig_verify(b"message", b"signature", b"key"),
+            Ok(true)
+        );
+        assert_eq!(ledger.sig_verify(b"other", b"signature", b"key"), Ok(false));
+
+        ledger.set_sig_verifier(Rc::new(|message: &[u8], _: &[u8], _: &[u8]| {
+            message == b"other"
+        }));
+        assert_eq!(
+            ledger.sig_verify(b"message", b"signature", b"key"),
+            Ok(false)
+        );
+        assert_eq!(ledger.sig_verify(b"other", b"signature", b"key"), Ok(true));
+    }
+}
@@ -5,10 +5,16 @@ pub mod payment_id;
 pub mod payments;
 pub mod utils;
 
-use crate::vm::Vm;
+use crate::{
+    error::{Error, Result, RuntimeError},
+    vm::Vm,
+};
 use params::Params;
 use payments::Payments;
-use wasmi::Memory;
+use wasmi::{core::Pages, AsContextMut, Memory, TypedFunc};
+
+/// Size of a page of linear memory in bytes.
+const PAGE_SIZE: usize = 65536;
 
 /// A structure accessible within the WASM interpreter to access linear memory,
 /// call stack, heap address, and other necessary information.
@@ -16,6 +22,9 @@ pub struct Runtime<'a> {
     memory: Option<Memory>,
     pub vm: &'a mut Vm,
     heap_base: i32,
+    allocator: Option<TypedFunc<u32, u32>>,
+    /// Fuel charged for each byte written to memory by the called host function.
+    output_cost: u64,
     pub params: Params,
     pub payments: Payments,
 }
@@ -26,6 +35,8 @@ impl<'a> Runtime<'a> {
             memory: None,
             vm,
             heap_base: 0,
+            allocator: None,
+            output_cost: 0,
             params: Params::new(),
             payments: Payments::new(),
         }
@@ -50,4 +61,69 @@ impl<'a> Runtime<'a> {
     pub fn set_heap_base(&mut self, value: i32) {
         self.heap_base = value;
     }
+
+    /// Getting the `allocate(size) -> ptr` function exported by the contract.
+    pub fn allocator(&self) -> Option<TypedFunc<u32, u32>> {
+        self.allocator
+    }
+
+    /// Setting the allocator of the contract.
+    pub fn set_allocator(&mut self, allocator: TypedFunc<u32, u32>) {
+        self.allocator = Some(allocator);
+    }
+
+    /// Getting the fuel charged for each byte of the output of the called host function.
+    pub fn output_cost(&self) -> u64 {
+        self.output_cost
+    }
+
+    /// Setting the fuel charged for each byte of the output, on each host function call.
+    pub fn set_output_cost(&mut self, value: u64) {
+        self.output_cost = value;
+    }
+}
+
+/// Allocating a region of linear memory for data passed to the contract.
+///
+/// If the contract exports `allocate(size) -> ptr`, the region is requested from it,
+/// the contract is responsible for growing its memory.
+/// Otherwise the region is placed at the heap address, which is moved past it,
+/// and the memory is grown if the region does not fit.
+pub fn allocate<'a>(
+    mut ctx: impl AsContextMut<UserState = Runtime<'a>>,
+    size: usize,
+) -> Result<usize> {
+    let (memory, allocator, heap_base) = {
+        let context = ctx.as_context();
+        let runtime = context.data();
+        (runtime.memory(), runtime.allocator(), runtime.heap_base())
+    };
+    let memory = memory.ok_or(Error::Runtime(RuntimeError::MemoryNotFound))?;
+
+    if let Some(allocator) = allocator {
+        let size =
+            u32::try_from(size).map_err(|_| Error::Runtime(RuntimeError::MemoryOutOfBounds))?;
+        let offset = allocator
+            .call(&mut ctx, size)
+            .map_err(|error| Error::Runtime(RuntimeError::AllocationFailed(error.to_string())))?;
+        return Ok(offset as usize);
+    }
+
+    let offset = heap_base as u32 as usize;
+    let end = offset
+        .checked_add(size)
+        .filter(|end| *end <= i32::MAX as usize)
+        .ok_or(Error::Runtime(RuntimeError::MemoryOutOfBounds))?;
+
+    let length = memory.data(&ctx).len();
+    if end > length {
+        let pages = Pages::new((end - length).div_ceil(PAGE_SIZE) as u32)
+            .ok_or(Error::Runtime(RuntimeError::MemoryOutOfBounds))?;
+        memory
+            .grow(&mut ctx, pages)
+            .map_err(|_| Error::Runtime(RuntimeError::MemoryOutOfBounds))?;
+    }
+
+    ctx.as_context_mut().data_mut().set_heap_base(end as i32);
+    Ok(offset)
 }
@@ -1,14 +1,41 @@
 use crate::{
-    error::{Error, ExecutableError, Result},
+    error::{Error, ExecutableError, Result, RuntimeError},
+    exec::abi::Function,
     runtime::utils,
 };
+use wasmi::Value;
 
-#[derive(Debug, Eq, PartialEq)]
+/// Maximum nesting of lists and maps.
+pub const MAX_DEPTH: usize = 16;
+
+/// Type tags of the `DataEntry` values.
+pub const TAG_INTEGER: u8 = 0;
+pub const TAG_BOOLEAN: u8 = 1;
+pub const TAG_BINARY: u8 = 2;
+pub const TAG_STRING: u8 = 3;
+pub const TAG_UNSIGNED_INTEGER: u8 = 4;
+pub const TAG_INTEGER128: u8 = 5;
+pub const TAG_INTEGER256: u8 = 6;
+pub const TAG_LIST: u8 = 7;
+pub const TAG_MAP: u8 = 8;
+/// Tombstone of a deleted key.
+pub const TAG_DELETED: u8 = 255;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum DataEntry {
     Integer(i64),
     Boolean(i32),
     Binary(Vec<u8>),
     String(Vec<u8>),
+    UnsignedInteger(u64),
+    Integer128(i128),
+    /// Signed 256-bit integer, big-endian two's complement.
+    Integer256([u8; 32]),
+    List(Vec<DataEntry>),
+    /// Entries in the order they were written, keys are not required to be unique.
+    Map(Vec<(Vec<u8>, DataEntry)>),
+    /// Tombstone of a deleted key, only used in the state diff.
+    Deleted,
 }
 
 impl DataEntry {
@@ -23,28 +50,121 @@ impl DataEntry {
             None => result.extend_from_slice(&0u16.to_be_bytes()),
         }
 
+        self.put_value(&mut result);
+
+        result
+    }
+
+    fn put_value(&self, result: &mut Vec<u8>) {
         match self {
             Self::Integer(value) => {
-                result.push(0u8);
+                result.push(TAG_INTEGER);
                 result.extend_from_slice(&value.to_be_bytes());
             }
             Self::Boolean(value) => {
-                result.push(1u8);
+                result.push(TAG_BOOLEAN);
                 result.push(*value as u8);
             }
             Self::Binary(value) => {
-                result.push(2u8);
+                result.push(TAG_BINARY);
                 result.extend_from_slice(&(value.len() as u32).to_be_bytes());
                 result.extend_from_slice(value);
             }
             Self::String(value) => {
-                result.push(3u8);
+                result.push(TAG_STRING);
                 result.extend_from_slice(&(value.len() as u32).to_be_bytes());
                 result.extend_from_slice(value);
             }
+            Self::UnsignedInteger(value) => {
+                result.push(TAG_UNSIGNED_INTEGER);
+                result.extend_from_slice(&value.to_be_bytes());
+            }
+            Self::Integer128(value) => {
+                result.push(TAG_INTEGER128);
+                result.extend_from_slice(&value.to_be_bytes());
+            }
+            Self::Integer256(value) => {
+                result.push(TAG_INTEGER256);
+                result.extend_from_slice(value);
+            }
+            Self::List(values) => {
+                result.push(TAG_LIST);
+                result.extend_from_slice(&(values.len() as u16).to_be_bytes());
+                for value in values {
+                    value.put_value(result);
+                }
+            }
+            Self::Map(entries) => {
+                result.push(TAG_MAP);
+                result.extend_from_slice(&(entries.len() as u16).to_be_bytes());
+                for (key, value) in entries {
+                    result.extend_from_slice(&(key.len() as u16).to_be_bytes());
+                    result.extend_from_slice(key);
+                    value.put_value(result);
+                }
+            }
+            Self::Deleted => result.push(TAG_DELETED),
         }
+    }
 
-        result
+    /// Splitting the value into the type tag and the value bytes as they are written
+    /// to the memory of the contract, integers are big-endian.
+    ///
+    /// Lists are written as the `u16` count followed by the values with their type tags,
+    /// maps as the `u16` count followed by the keys prefixed with the `u16` length
+    /// and the values with their type tags.
+    pub fn into_tagged(self) -> (u8, Vec<u8>) {
+        match self {
+            Self::Integer(value) => (TAG_INTEGER, value.to_be_bytes().to_vec()),
+            Self::Boolean(value) => (TAG_BOOLEAN, vec![value as u8]),
+            Self::Binary(value) => (TAG_BINARY, value),
+            Self::String(value) => (TAG_STRING, value),
+            Self::UnsignedInteger(value) => (TAG_UNSIGNED_INTEGER, value.to_be_bytes().to_vec()),
+            Self::Integer128(value) => (TAG_INTEGER128, value.to_be_bytes().to_vec()),
+            Self::Integer256(value) => (TAG_INTEGER256, value.to_vec()),
+            value @ (Self::List(_) | Self::Map(_)) => {
+                let mut bytes = vec![];
+                value.put_value(&mut bytes);
+                (bytes[0], bytes.split_off(1))
+            }
+            Self::Deleted => (TAG_DELETED, vec![]),
+        }
+    }
+
+    pub fn type_tag(&self) -> u8 {
+        match self {
+            Self::Integer(_) => TAG_INTEGER,
+            Self::Boolean(_) => TAG_BOOLEAN,
+            Self::Binary(_) => TAG_BINARY,
+            Self::String(_) => TAG_STRING,
+            Self::UnsignedInteger(_) => TAG_UNSIGNED_INTEGER,
+            Self::Integer128(_) => TAG_INTEGER128,
+            Self::Integer256(_) => TAG_INTEGER256,
+            Self::List(_) => TAG_LIST,
+            Self::Map(_) => TAG_MAP,
+            Self::Deleted => TAG_DELETED,
+        }
+    }
+
+    /// Making the value of the type from the bytes as they are written
+    /// to the memory of the contract, the reverse of `into_tagged`.
+    pub fn from_tagged(tag: u8, bytes: &[u8]) -> Result<Self> {
+        match tag {
+            TAG_BINARY => Ok(Self::Binary(bytes.to_vec())),
+            TAG_STRING => Ok(Self::String(bytes.to_vec())),
+            TAG_DELETED => Err(Error::Executable(ExecutableError::FailedDeserialize)),
+            _ => {
+                let mut input = vec![tag];
+                input.extend_from_slice(bytes);
+
+                let mut offset_input: usize = 0;
+                let value = Self::get_value(&input, &mut offset_input)?;
+                match offset_input == input.len() {
+                    true => Ok(value),
+                    false => Err(Error::Executable(ExecutableError::FailedDeserialize)),
+                }
+            }
+        }
     }
 
     pub fn deserialize(input: &[u8]) -> Result<Self> {
@@ -54,43 +174,143 @@ impl DataEntry {
         Self::get_value(input, &mut offset_input)
     }
 
+    /// Deserializing the value together with its key.
+    pub fn deserialize_with_key(input: &[u8]) -> Result<(Vec<u8>, Self)> {
+        let mut offset_input: usize = 0;
+
+        let length = utils::get_u16(input, &mut offset_input)?;
+        let key = utils::get_bytes(input, &mut offset_input, length as usize)?;
+        let value = Self::get_value(input, &mut offset_input)?;
+
+        Ok((key, value))
+    }
+
+    /// Splitting a list of values serialized with their keys and prefixed with the `u16` count,
+    /// as passed to `set_storage_batch`, into separately serialized values.
+    pub fn split_batch(input: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let mut offset_input: usize = 0;
+
+        let count = utils::get_u16(input, &mut offset_input)?;
+
+        let mut result = vec![];
+        for _ in 0..count {
+            let start = offset_input;
+            Self::skip_key(input, &mut offset_input)?;
+            if let Self::Deleted = Self::get_value(input, &mut offset_input)? {
+                return Err(Error::Executable(ExecutableError::FailedDeserialize));
+            }
+
+            let value = input
+                .get(start..offset_input)
+                .ok_or(Error::Executable(ExecutableError::FailedDeserialize))?;
+            result.push(value.to_vec());
+        }
+
+        Ok(result)
+    }
+
+    /// Joining serialized values into a list prefixed with the `u16` count.
+    pub fn join_batch(values: &[Vec<u8>]) -> Vec<u8> {
+        let mut result = (values.len() as u16).to_be_bytes().to_vec();
+        for value in values {
+            result.extend_from_slice(value);
+        }
+        result
+    }
+
+    /// Deserializing values serialized with their keys and prefixed with the `u16` count,
+    /// in the format of the function arguments. Empty input gives no values.
+    pub fn deserialize_list(input: &[u8]) -> Result<Vec<Self>> {
+        let mut offset_input: usize = 0;
+
+        let count = match input.is_empty() {
+            true => 0,
+            false => utils::get_u16(input, &mut offset_input)?,
+        };
+
+        let mut result = vec![];
+        for _ in 0..count {
+            Self::skip_key(input, &mut offset_input)?;
+            result.push(Self::get_value(input, &mut offset_input)?);
+        }
+
+        match offset_input == input.len() {
+            true => Ok(result),
+            false => Err(Error::Executable(ExecutableError::FailedDeserialize)),
+        }
+    }
+
+    /// Deserializing the function arguments into the values of the parameters.
+    /// If the function is described by the ABI, the arguments are checked against it.
+    ///
+    /// Integers are passed as `i64` and booleans as `i32`. Other values are written
+    /// to memory starting from `offset_output` as in `into_tagged`,
+    /// and passed as the offset and length of type `i32`.
     pub fn deserialize_params(
         input: &[u8],
         output: &mut [u8],
         offset_output: &mut usize,
-    ) -> Result<Vec<String>> {
+        abi: Option<&Function>,
+    ) -> Result<Vec<Value>> {
         let mut offset_input: usize = 0;
 
-        let mut params: Vec<String> = vec![];
+        let mut params: Vec<Value> = vec![];
 
-        if input.is_empty() {
-            return Ok(params);
+        let count = match input.is_empty() {
+            true => 0,
+            false => utils::get_u16(input, &mut offset_input)?,
+        };
+
+        if let Some(abi) = abi {
+            if abi.params.len() != count as usize {
+                return Err(Error::Executable(ExecutableError::FailedParseFuncArgs(
+                    format!(
+                        "Function {} takes {} arguments, given {}",
+                        abi.name,
+                        abi.params.len(),
+                        count
+                    ),
+                )));
+            }
         }
 
-        let mut count = utils::get_u16(input, &mut offset_input)?;
-        while count > 0 {
+        for index in 0..count as usize {
             Self::skip_key(input, &mut offset_input)?;
-            match Self::get_value(input, &mut offset_input)? {
-                Self::Integer(value) => params.push(format!("{}", value)),
-                Self::Boolean(value) => params.push(format!("{}", value)),
-                Self::Binary(value) => {
-                    let length = value.len();
-                    let offset_o = *offset_output;
-                    output[offset_o..offset_o + length].copy_from_slice(value.as_slice());
-                    params.push(format!("{}", *offset_output));
-                    params.push(format!("{}", length));
-                    *offset_output += length;
+            let value = Self::get_value(input, &mut offset_input)?;
+
+            if let Some(param) = abi.map(|abi| &abi.params[index]) {
+                if param.type_tag != value.type_tag() {
+                    return Err(Error::Executable(ExecutableError::FailedParseFuncArgs(
+                        format!(
+                            "Argument {} ({}): expected type {}, found {}",
+                            index,
+                            param.name,
+                            param.type_tag,
+                            value.type_tag()
+                        ),
+                    )));
                 }
-                Self::String(value) => {
+            }
+
+            match value {
+                Self::Integer(value) => params.push(Value::I64(value)),
+                Self::Boolean(value) => params.push(Value::I32(value)),
+                // Passed with the same bits
+                Self::UnsignedInteger(value) => params.push(Value::I64(value as i64)),
+                Self::Deleted => return Err(Error::Executable(ExecutableError::FailedDeserialize)),
+                value => {
+                    let (_, value) = value.into_tagged();
                     let length = value.len();
                     let offset_o = *offset_output;
-                    output[offset_o..offset_o + length].copy_from_slice(value.as_slice());
-                    params.push(format!("{}", *offset_output));
-                    params.push(format!("{}", length));
+                    output
+                        .get_mut(offset_o..offset_o + length)
+                        .ok_or(Error::Runtime(RuntimeError::MemoryOutOfBounds))?
+                        .copy_from_slice(value.as_slice());
+                    params.push(Value::I32(offset_o as i32));
+                    params.push(Value::I32(length as i32));
                     *offset_output += length;
                 }
             }
-            count -= 1;
         }
 
         Ok(params)
@@ -103,36 +323,89 @@ impl DataEntry {
     }
 
     fn get_value(input: &[u8], offset: &mut usize) -> Result<Self> {
+        Self::get_nested_value(input, offset, 0)
+    }
+
+    fn get_nested_value(input: &[u8], offset: &mut usize, depth: usize) -> Result<Self> {
         let byte = utils::get_u8(input, offset)?;
 
         match byte {
-            0u8 => {
+            TAG_INTEGER => {
                 let integer = utils::get_u64(input, offset)?;
                 Ok(Self::Integer(integer as i64))
             }
-            1u8 => {
+            TAG_BOOLEAN => {
                 let boolean = utils::get_u8(input, offset)?;
                 Ok(Self::Boolean(boolean as i32))
             }
-            2u8 => {
+            TAG_BINARY => {
                 let length = utils::get_u32(input, offset)?;
                 let binary = utils::get_bytes(input, offset, length as usize)?;
                 Ok(Self::Binary(binary))
             }
-            3u8 => {
+            TAG_STRING => {
                 let length = utils::get_u32(input, offset)?;
                 let string = utils::get_bytes(input, offset, length as usize)?;
                 Ok(Self::String(string))
             }
+            TAG_UNSIGNED_INTEGER => {
+                let integer = utils::get_u64(input, offset)?;
+                Ok(Self::UnsignedInteger(integer))
+            }
+            TAG_INTEGER128 => {
+                let bytes = utils::get_bytes(input, offset, 16)?;
+                let integer = i128::from_be_bytes(
+                    bytes
+                        .try_into()
+                        .map_err(|_| Error::Executable(ExecutableError::FailedDeserialize))?,
+                );
+                Ok(Self::Integer128(integer))
+            }
+            TAG_INTEGER256 => {
+                let bytes = utils::get_bytes(input, offset, 32)?;
+                let integer = bytes
+                    .try_into()
+                    .map_err(|_| Error::Executable(ExecutableError::FailedDeserialize))?;
+                Ok(Self::Integer256(integer))
+            }
+            TAG_LIST | TAG_MAP if depth >= MAX_DEPTH => {
+                Err(Error::Executable(ExecutableError::FailedDeserialize))
+            }
+            TAG_LIST => {
+                let count = utils::get_u16(input, offset)?;
+                let mut values = vec![];
+                for _ in 0..count {
+                    values.push(Self::get_nested_item(input, offset, depth + 1)?);
+                }
+                Ok(Self::List(values))
+            }
+            TAG_MAP => {
+                let count = utils::get_u16(input, offset)?;
+                let mut entries = vec![];
+                for _ in 0..count {
+                    let length = utils::get_u16(input, offset)?;
+                    let key = utils::get_bytes(input, offset, length as usize)?;
+                    entries.push((key, Self::get_nested_item(input, offset, depth + 1)?));
+                }
+                Ok(Self::Map(entries))
+            }
+            TAG_DELETED => Ok(Self::Deleted),
             _ => Err(Error::Executable(ExecutableError::FailedDeserialize)),
         }
     }
+
+    /// Getting an item of a list or map, which cannot be a tombstone.
+    fn get_nested_item(input: &[u8], offset: &mut usize, depth: usize) -> Result<Self> {
+        match Self::get_nested_value(input, offset, depth)? {
+            Self::Deleted => Err(Error::Executable(ExecutableError::FailedDeserialize)),
+            value => Ok(value),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::str::FromStr;
 
     #[test]
     fn test_deserialize() {
@@ -163,6 +436,65 @@ mod tests {
         assert_eq!(result, DataEntry::String(vec.clone()));
     }
 
+    #[test]
+    fn test_extended_types() {
+        let mut integer256 = [0xffu8; 32];
+        integer256[31] = 0xfe;
+
+        let values = vec![
+            DataEntry::UnsignedInteger(u64::MAX),
+            DataEntry::Integer128(-2),
+            DataEntry::Integer256(integer256),
+            DataEntry::List(vec![
+                DataEntry::Integer(1),
+                DataEntry::List(vec![DataEntry::String(b"nested".to_vec())]),
+            ]),
+            DataEntry::Map(vec![
+                (b"a".to_vec(), DataEntry::Boolean(1)),
+                (b"b".to_vec(), DataEntry::Map(vec![])),
+            ]),
+        ];
+
+        for value in values {
+            let result = DataEntry::deserialize(&value.serialize(Some(b"key")))
+                .expect("Error deserialize DataEntry");
+            assert_eq!(result, value);
+
+            let (tag, bytes) = value.clone().into_tagged();
+            let result = DataEntry::from_tagged(tag, &bytes).expect("Error from tagged");
+            assert_eq!(result, value);
+        }
+
+        let (tag, bytes) = DataEntry::List(vec![DataEntry::Boolean(0)]).into_tagged();
+        assert_eq!((tag, bytes), (TAG_LIST, vec![0, 1, 1, 0]));
+
+        // Trailing bytes, tombstones in a list and too deep nesting are rejected
+        assert!(DataEntry::from_tagged(TAG_UNSIGNED_INTEGER, &[0; 9]).is_err());
+        assert!(DataEntry::from_tagged(TAG_LIST, &[0, 1, TAG_DELETED]).is_err());
+
+        let mut value = DataEntry::List(vec![]);
+        for _ in 0..MAX_DEPTH {
+            value = DataEntry::List(vec![value]);
+        }
+        assert!(DataEntry::deserialize(&value.serialize(None)).is_err());
+    }
+
+    #[test]
+    fn test_batch() {
+        let values = vec![
+            DataEntry::Integer(1).serialize(Some(b"a")),
+            DataEntry::String(b"value".to_vec()).serialize(Some(b"b")),
+        ];
+
+        let input = DataEntry::join_batch(&values);
+        let result = DataEntry::split_batch(&input).expect("Error split batch");
+        assert_eq!(result, values);
+
+        let input = DataEntry::join_batch(&[DataEntry::Deleted.serialize(Some(b"a"))]);
+        assert!(DataEntry::split_batch(&input).is_err());
+        assert!(DataEntry::split_batch(&[0, 1]).is_err());
+    }
+
     #[test]
     fn test_deserialize_params() {
         let input = [
@@ -176,21 +508,35 @@ mod tests {
         let mut memory = [0u8; 1000];
         let mut offset_memory = 100;
 
-        let result = DataEntry::deserialize_params(&input, &mut memory, &mut offset_memory)
+        let result = DataEntry::deserialize_params(&input, &mut memory, &mut offset_memory, None)
             .expect("Error deserialize DataEntry");
 
         assert_eq!(result.len(), 6);
-        assert_eq!(result[0], "1");
-        assert_eq!(result[1], "1");
+        assert_eq!(result[0].i64(), Some(1));
+        assert_eq!(result[1].i32(), Some(1));
 
         let data = [116, 101, 115, 116, 95, 118, 97, 108, 117, 101];
 
-        let offset = usize::from_str(&result[2]).expect("Failed usize from_str");
-        let length = usize::from_str(&result[3]).expect("Failed usize from_str");
+        let offset = result[2].i32().expect("Not i32") as usize;
+        let length = result[3].i32().expect("Not i32") as usize;
         assert_eq!(memory[offset..offset + length], data);
 
-        let offset = usize::from_str(&result[4]).expect("Failed usize from_str");
-        let length = usize::from_str(&result[5]).expect("Failed usize from_str");
+        let offset = result[4].i32().expect("Not i32") as usize;
+        let length = result[5].i32().expect("Not i32") as usize;
         assert_eq!(memory[offset..offset + length], data);
+
+        let mut input = vec![0, 2];
+        input.extend(DataEntry::UnsignedInteger(u64::MAX).serialize(None));
+        input.extend(DataEntry::Integer128(1).serialize(None));
+
+        let result = DataEntry::deserialize_params(&input, &mut memory, &mut offset_memory, None)
+            .expect("Error deserialize DataEntry");
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].i64(), Some(-1));
+
+        let offset = result[1].i32().expect("Not i32") as usize;
+        let length = result[2].i32().expect("Not i32") as usize;
+        assert_eq!(memory[offset..offset + length], 1i128.to_be_bytes());
     }
 }
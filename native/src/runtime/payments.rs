@@ -1,4 +1,13 @@
+use crate::{
+    error::{Error, ExecutableError, Result},
+    runtime::utils,
+};
+
+/// Length of the asset identifier in serialized payments.
+const ASSET_ID_LENGTH: usize = 32;
+
 /// Structure allowing to accumulate payments for calling a contract function.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Payments(pub Vec<(Vec<u8>, i64)>);
 
 impl Default for Payments {
@@ -40,6 +49,26 @@ impl Payments {
         result
     }
 
+    /// Parsing the byte representation of function payments.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let mut offset = 0;
+        let count = utils::get_u16(bytes, &mut offset)?;
+
+        let mut payments = Self::new();
+        for _ in 0..count {
+            let asset_id = match utils::get_u8(bytes, &mut offset)? {
+                0 => vec![],
+                1 => utils::get_bytes(bytes, &mut offset, ASSET_ID_LENGTH)?,
+                _ => return Err(Error::Executable(ExecutableError::FailedDeserialize)),
+            };
+            let amount = utils::get_u64(bytes, &mut offset)? as i64;
+
+            payments.push(&asset_id, amount);
+        }
+
+        Ok(payments)
+    }
+
     /// Adding an payment to call the contract function.
     pub fn push(&mut self, asset_id: &[u8], amount: i64) {
         self.0.push((asset_id.to_vec(), amount));
@@ -74,5 +103,9 @@ mod tests {
         );
 
         assert_eq!(payments.as_bytes(), BYTES.to_vec());
+        assert_eq!(
+            Payments::deserialize(&BYTES).expect("Error deserialize payments"),
+            payments
+        );
     }
 }
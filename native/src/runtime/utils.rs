@@ -50,3 +50,26 @@ pub fn get_bytes(input: &[u8], offset: &mut usize, length: usize) -> Result<Vec<
         None => Err(Error::Executable(ExecutableError::FailedDeserialize)),
     }
 }
+
+/// Getting a list of byte arrays, prefixed with the `u16` count, each array prefixed with the `u16` length.
+pub fn get_list(input: &[u8], offset: &mut usize) -> Result<Vec<Vec<u8>>> {
+    let count = get_u16(input, offset)?;
+
+    let mut result = vec![];
+    for _ in 0..count {
+        let length = get_u16(input, offset)?;
+        result.push(get_bytes(input, offset, length as usize)?);
+    }
+
+    Ok(result)
+}
+
+/// Serializing a list of byte arrays in the format read by [`get_list`].
+pub fn serialize_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let mut result = (items.len() as u16).to_be_bytes().to_vec();
+    for item in items {
+        result.extend_from_slice(&(item.len() as u16).to_be_bytes());
+        result.extend_from_slice(item);
+    }
+    result
+}
@@ -1,11 +1,20 @@
 use crate::{
-    error::{Error, ExecutableError, Result},
-    vm::Vm,
+    error::{Error, ExecutableError, Result, RuntimeError},
+    exec::abi::{Abi, Function, SECTION_NAME},
+    node::ledger::Ledger,
+    runtime::data_entry::{DataEntry, TAG_INTEGER, TAG_STRING},
+    vm::{
+        config::VmConfig,
+        cost_schedule::{Cost, CostSchedule},
+        error_report::{ErrorCategory, ErrorFrame, ErrorReport},
+        Vm,
+    },
 };
-use jni::{InitArgsBuilder, JNIVersion, JavaVM};
 use wasmi::Value;
 use wevm_proc_macro::module;
 
+const CONTRACT_ID: [u8; 32] = [7; 32];
+
 /// Converts the given `.wat` into `.wasm`.
 pub fn wat2wasm(wat: &str) -> Result<Vec<u8>, wat::Error> {
     wat::parse_str(wat)
@@ -23,6 +32,18 @@ mod test {
         |mut _caller: Caller<Runtime>| 42
     }
 
+    fn test_panic() {
+        |mut _caller: Caller<Runtime>| {
+            panic!("Test panic");
+        }
+    }
+
+    fn test_sleep(millis: u32) {
+        |mut _caller: Caller<Runtime>| {
+            std::thread::sleep(std::time::Duration::from_millis(millis as u64));
+        }
+    }
+
     fn test_memory(offset: u32, length: u32) {
         |mut caller: Caller<Runtime>| {
             let (memory, _ctx) = caller
@@ -38,56 +59,56 @@ mod test {
             assert_eq!("Hi", result);
         }
     }
+
+    #[cost(bytes = "length", entries = "limit")]
+    fn test_charged(_offset: *const u8, length: usize, limit: u32) {
+        |mut _caller: Caller<Runtime>| {}
+    }
 }
 
 struct TestRunner {
-    java_vm: JavaVM,
+    ledger: Ledger,
+    cost_schedule: CostSchedule,
+    config: VmConfig,
 }
 
 impl TestRunner {
     pub fn new() -> Self {
-        // Preparing a fake jvm to initialize the call stack
-        let jvm_args = InitArgsBuilder::new()
-            .version(JNIVersion::V8)
-            .option("-Xcheck:jni")
-            .build()
-            .expect("Failed to initialize JVM args");
-        let java_vm = JavaVM::new(jvm_args).expect("JavaVM initialization failed");
-
-        Self { java_vm }
+        Self {
+            ledger: Ledger::new(b'T' as i8),
+            cost_schedule: CostSchedule::default(),
+            config: VmConfig {
+                memory: (1, 1),
+                ..Default::default()
+            },
+        }
     }
 
     pub fn run(&self, wat: &str, params: Vec<u8>) -> Result<Vec<Value>> {
-        // Preparing a fake jvm to initialize the call stack
-        let env = self
-            .java_vm
-            .attach_current_thread()
-            .expect("Failed attaches the current thread to the Java VM");
-
-        let jvm = env
-            .get_java_vm()
-            .expect("Failed receiving JavaVM interface");
-        let array = env.new_byte_array(1).expect("Array creation failed");
-        let global_ref = env
-            .new_global_ref(array)
-            .expect("Error callback new_global_ref");
+        self.call(wat, "_constructor", params)
+    }
+
+    pub fn call(&self, wat: &str, func_name: &str, params: Vec<u8>) -> Result<Vec<Value>> {
+        self.vm(wat).run(func_name, &params)
+    }
 
+    pub fn vm(&self, wat: &str) -> Vm {
         let bytecode = wat2wasm(wat).expect("WAT code parsing failed");
-        let memory: (u32, u32) = (1, 1);
         let fuel_limit = 1024;
 
-        let mut stack = Vm::new(
-            vec![],
+        let mut modules = modules::modules();
+        modules.extend(crate::modules());
+
+        Vm::new(
+            CONTRACT_ID.to_vec(),
             bytecode,
-            memory,
+            self.config.clone(),
             fuel_limit,
-            modules::modules(),
-            Some(jvm),
-            Some(global_ref),
+            modules,
+            self.cost_schedule.clone(),
+            self.ledger.clone(),
         )
-        .expect("Call stack creation failed");
-
-        stack.run("_constructor", &params)
+        .expect("Call stack creation failed")
     }
 }
 
@@ -224,10 +245,1956 @@ fn test_vm() {
             ],
         );
 
-        if let Err(Error::Executable(ExecutableError::InvalidBytecode(_))) = result {
-            assert!(true);
-        } else {
-            assert!(false);
-        }
+        assert!(matches!(
+            result,
+            Err(Error::Executable(ExecutableError::InvalidBytecode(_)))
+        ));
+    }
+}
+
+#[test]
+fn test_ledger() {
+    let runner = TestRunner::new();
+
+    // Storage test
+    {
+        let wat = r#"
+        (module
+            (import "env0" "set_storage_int" (func $set_storage_int (param i32 i32 i64) (result i32)))
+            (import "env0" "get_storage_int" (func $get_storage_int (param i32 i32 i32 i32) (result i32 i64)))
+
+            (import "env" "memory" (memory 1 1))
+
+            (func (export "_constructor") (result i32)
+                (call $set_storage_int
+                    (i32.const 0)
+                    (i32.const 3)
+                    (i64.const 42))
+            )
+
+            (func (export "increment") (result i32)
+                (local $error i32) (local $value i64)
+                (call $get_storage_int
+                    (i32.const 0)
+                    (i32.const 0)
+                    (i32.const 0)
+                    (i32.const 3))
+                (local.set $value)
+                (local.tee $error)
+                (if (then (return (local.get $error))))
+
+                (call $set_storage_int
+                    (i32.const 0)
+                    (i32.const 3)
+                    (i64.add (local.get $value) (i64.const 1)))
+            )
+
+            (global $__heap_base (export "__heap_base") i32 (i32.const 3))
+
+            (data (i32.const 0) "key")
+        )
+        "#;
+
+        let result = runner.run(wat, vec![]).expect("Constructor failed");
+        assert_eq!(result[0].i32(), Some(0));
+        assert_eq!(
+            runner.ledger.storage(&CONTRACT_ID, b"key"),
+            Some(DataEntry::Integer(42))
+        );
+
+        let result = runner.call(wat, "increment", vec![]).expect("Call failed");
+        assert_eq!(result[0].i32(), Some(0));
+        assert_eq!(
+            runner.ledger.storage(&CONTRACT_ID, b"key"),
+            Some(DataEntry::Integer(43))
+        );
+    }
+
+    // Transfer test
+    {
+        let wat = r#"
+        (module
+            (import "env0" "transfer" (func $transfer (param i32 i32 i32 i32 i64) (result i32)))
+
+            (import "env" "memory" (memory 1 1))
+
+            (func (export "_constructor") (result i32)
+                (call $transfer
+                    (i32.const 0)
+                    (i32.const 0)
+                    (i32.const 0)
+                    (i32.const 26)
+                    (i64.const 300))
+            )
+
+            (global $__heap_base (export "__heap_base") i32 (i32.const 26))
+
+            (data (i32.const 0) "\01\54\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa")
+        )
+        "#;
+
+        let recipient = {
+            let mut bytes = vec![1, b'T'];
+            bytes.extend([0xaa; 24]);
+            bytes
+        };
+
+        runner.ledger.set_balance(&[], &CONTRACT_ID, 100);
+
+        let result = runner.run(wat, vec![]).expect("Constructor failed");
+        assert_ne!(result[0].i32(), Some(0));
+        assert_eq!(runner.ledger.balance(&[], &CONTRACT_ID), 100);
+
+        runner.ledger.set_balance(&[], &CONTRACT_ID, 1000);
+
+        let result = runner.run(wat, vec![]).expect("Constructor failed");
+        assert_eq!(result[0].i32(), Some(0));
+        assert_eq!(runner.ledger.balance(&[], &CONTRACT_ID), 700);
+        assert_eq!(runner.ledger.balance(&[], &recipient), 300);
+    }
+}
+
+const BURN_CONTRACT_ID: [u8; 32] = [8; 32];
+
+const BURN_CONTRACT: &str = r#"
+(module
+    (func (export "_constructor") (result i32)
+        (i32.const 0)
+    )
+
+    ;; Burns about 400 units of fuel
+    (func (export "burn") (result i32)
+        (local $i i32)
+        (local.set $i (i32.const 80))
+        (loop $loop
+            (br_if $loop
+                (local.tee $i
+                    (i32.sub (local.get $i) (i32.const 1)))))
+        (i32.const 0)
+    )
+
+    (global $__heap_base (export "__heap_base") i32 (i32.const 0))
+)
+"#;
+
+#[test]
+fn test_fuel_shared_between_frames() {
+    let runner = TestRunner::new();
+
+    runner.ledger.set_bytecode(
+        &BURN_CONTRACT_ID,
+        wat2wasm(BURN_CONTRACT).expect("WAT code parsing failed"),
+    );
+
+    // A single call fits into the fuel limit
+    let result = runner
+        .call(BURN_CONTRACT, "burn", vec![])
+        .expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    let caller = r#"
+    (module
+        (import "env0" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func $burn (result i32)
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 32)
+                (i32.const 4))
+        )
+
+        (func (export "_constructor") (result i32)
+            (drop (call $burn))
+            (drop (call $burn))
+            (call $burn)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 36))
+
+        (data (i32.const 0) "\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08")
+        (data (i32.const 32) "burn")
+    )
+    "#;
+
+    // Three nested calls exceed the fuel limit of the whole execution
+    let result = runner.run(caller, vec![]).expect("Call failed");
+    assert_eq!(
+        result[0].i32(),
+        Some(ExecutableError::FailedExec(String::new()).as_i32())
+    );
+}
+
+#[test]
+fn test_fuel_report() {
+    let runner = TestRunner::new();
+
+    runner.ledger.set_bytecode(
+        &BURN_CONTRACT_ID,
+        wat2wasm(BURN_CONTRACT).expect("WAT code parsing failed"),
+    );
+
+    let caller = r#"
+    (module
+        (import "env0" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 32)
+                (i32.const 4))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 36))
+
+        (data (i32.const 0) "\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08\08")
+        (data (i32.const 32) "burn")
+    )
+    "#;
+
+    let mut vm = runner.vm(caller);
+    let result = vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    let report = vm.fuel_report();
+    assert_eq!(report.len(), 2);
+
+    assert_eq!(report[0].contract_id, CONTRACT_ID.to_vec());
+    assert_eq!(report[0].func_name, "_constructor");
+    assert_eq!(report[0].depth, 0);
+    assert_eq!(report[0].fuel_consumed, vm.fuel_consumed());
+
+    assert_eq!(report[1].contract_id, BURN_CONTRACT_ID.to_vec());
+    assert_eq!(report[1].func_name, "burn");
+    assert_eq!(report[1].depth, 1);
+    assert!(report[1].fuel_consumed > 0);
+    assert!(report[1].fuel_consumed < report[0].fuel_consumed);
+}
+
+#[test]
+fn test_host_function_cost() {
+    let mut runner = TestRunner::new();
+
+    let wat = r#"
+    (module
+        (import "env0" "test_charged" (func $test_charged (param i32 i32 i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (call $test_charged
+                (i32.const 0)
+                (i32.const 2)
+                (i32.const 3))
+
+            (i32.const 0)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 2))
+
+        (data (i32.const 0) "Hi")
+    )
+    "#;
+
+    let mut vm = runner.vm(wat);
+    vm.run("_constructor", &[]).expect("Call failed");
+    let fuel_consumed = vm.fuel_consumed();
+
+    runner
+        .cost_schedule
+        .set("env0", "test_charged", Cost::new(100, 10).with_per_entry(5));
+
+    let mut vm = runner.vm(wat);
+    vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(vm.fuel_consumed(), fuel_consumed + 135);
+
+    runner
+        .cost_schedule
+        .set("env0", "test_charged", Cost::new(1024, 0));
+
+    let result = runner.run(wat, vec![]);
+    assert!(matches!(
+        result,
+        Err(Error::Executable(ExecutableError::FailedExec(_)))
+    ));
+}
+
+#[test]
+fn test_host_function_output_cost() {
+    let mut runner = TestRunner::new();
+
+    let wat = r#"
+    (module
+        (import "env0" "sha256" (func $sha256 (param i32 i32) (result i32 i32 i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (call $sha256
+                (i32.const 0)
+                (i32.const 2))
+            (drop)
+            (drop)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 2))
+
+        (data (i32.const 0) "Hi")
+    )
+    "#;
+
+    let mut vm = runner.vm(wat);
+    let result = vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+    let fuel_consumed = vm.fuel_consumed();
+
+    // The hash written to memory is charged per byte along with the input
+    runner.cost_schedule.set("env0", "sha256", Cost::new(0, 10));
+
+    let mut vm = runner.vm(wat);
+    vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(vm.fuel_consumed(), fuel_consumed + 2 * 10 + 32 * 10);
+
+    // The input is paid, but the output is not, so the remaining fuel is exhausted
+    runner.cost_schedule.set("env0", "sha256", Cost::new(0, 40));
+
+    let mut vm = runner.vm(wat);
+    let result = vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(
+        result[0].i32(),
+        Some(ExecutableError::FailedExec(String::new()).as_i32())
+    );
+    assert_eq!(vm.fuel_consumed(), 1024);
+}
+
+#[test]
+fn test_memory_out_of_bounds() {
+    let runner = TestRunner::new();
+    let out_of_bounds = Some(RuntimeError::MemoryOutOfBounds.as_i32());
+
+    let wat = r#"
+    (module
+        (import "env0" "set_storage_int" (func $set_storage_int (param i32 i32 i64) (result i32)))
+        (import "env0" "get_storage_int" (func $get_storage_int (param i32 i32 i32 i32) (result i32 i64)))
+        (import "env0" "sha256" (func $sha256 (param i32 i32) (result i32 i32 i32)))
+        (import "env0" "join" (func $join (param i32 i32 i32 i32) (result i32 i32 i32)))
+        (import "env0" "transfer" (func $transfer (param i32 i32 i32 i32 i64) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (call $set_storage_int
+                (i32.const 65534)
+                (i32.const 3)
+                (i64.const 42))
+        )
+
+        (func (export "get_storage") (result i32)
+            (call $get_storage_int
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const -1))
+            (drop)
+        )
+
+        (func (export "hash") (result i32)
+            (call $sha256
+                (i32.const -16)
+                (i32.const 32))
+            (drop)
+            (drop)
+        )
+
+        (func (export "join") (result i32)
+            (call $join
+                (i32.const 0)
+                (i32.const 3)
+                (i32.const 65535)
+                (i32.const 65535))
+            (drop)
+            (drop)
+        )
+
+        (func (export "transfer") (result i32)
+            (call $transfer
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 65535)
+                (i32.const 26)
+                (i64.const 1))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 3))
+
+        (data (i32.const 0) "key")
+    )
+    "#;
+
+    for func_name in ["_constructor", "get_storage", "hash", "join", "transfer"] {
+        let result = runner.call(wat, func_name, vec![]).expect("Call failed");
+        assert_eq!(result[0].i32(), out_of_bounds, "{}", func_name);
+    }
+    assert_eq!(runner.ledger.storage(&CONTRACT_ID, b"key"), None);
+
+    // The result of the host function does not fit into memory
+    let wat = r#"
+    (module
+        (import "env0" "sha256" (func $sha256 (param i32 i32) (result i32 i32 i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (call $sha256
+                (i32.const 0)
+                (i32.const 3))
+            (drop)
+            (drop)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 65520))
+
+        (data (i32.const 0) "key")
+    )
+    "#;
+
+    let result = runner.run(wat, vec![]).expect("Call failed");
+    assert_eq!(result[0].i32(), out_of_bounds);
+
+    // Params do not fit into memory
+    let wat = r#"
+    (module
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (param i32 i32) (result i32)
+            (i32.const 0)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 65535))
+    )
+    "#;
+
+    let params = {
+        let mut bytes = vec![0, 1];
+        bytes.extend(DataEntry::Binary(vec![1; 16]).serialize(None));
+        bytes
+    };
+
+    let result = runner.run(wat, params);
+    assert!(matches!(
+        result,
+        Err(Error::Runtime(RuntimeError::MemoryOutOfBounds))
+    ));
+}
+
+#[test]
+fn test_invalid_lengths() {
+    let runner = TestRunner::new();
+    let converting = Some(RuntimeError::ConvertingNumericTypes.as_i32());
+    let out_of_bounds = Some(RuntimeError::MemoryOutOfBounds.as_i32());
+
+    let wat = r#"
+    (module
+        (import "env0" "to_int" (func $to_int (param i32 i32) (result i32 i64)))
+        (import "env0" "get_block_height" (func $get_block_height (result i32 i64)))
+        (import "env0" "drop" (func $drop (param i32 i32 i64) (result i32 i32 i32)))
+        (import "env0" "drop_right" (func $drop_right (param i32 i32 i64) (result i32 i32 i32)))
+        (import "env0" "take_right" (func $take_right (param i32 i32 i64) (result i32 i32 i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "to_int") (result i32)
+            (call $to_int
+                (i32.const 0)
+                (i32.const 3))
+            (drop)
+        )
+
+        (func (export "block") (result i32)
+            (call $get_block_height)
+            (drop)
+        )
+
+        (func (export "drop") (result i32)
+            (call $drop
+                (i32.const -1)
+                (i32.const 3)
+                (i64.const 2))
+            (drop)
+            (drop)
+        )
+
+        (func (export "drop_right") (result i32)
+            (call $drop_right
+                (i32.const 0)
+                (i32.const 3)
+                (i64.const 4))
+            (drop)
+            (drop)
+        )
+
+        (func (export "take_right") (result i32)
+            (call $take_right
+                (i32.const 0)
+                (i32.const 3)
+                (i64.const 4))
+            (drop)
+            (drop)
+        )
+
+        (func (export "_constructor") (result i32)
+            (i32.const 0)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 3))
+
+        (data (i32.const 0) "abc")
+    )
+    "#;
+
+    // The node answers with a field that is not 8 bytes long
+    runner.ledger.set_block_field("height", vec![0; 3]);
+
+    let call = |func_name: &str| {
+        let result = runner.call(wat, func_name, vec![]).expect("Call failed");
+        result[0].i32()
+    };
+
+    assert_eq!(call("to_int"), converting);
+    assert_eq!(call("block"), converting);
+    assert_eq!(call("drop"), out_of_bounds);
+    assert_eq!(call("drop_right"), out_of_bounds);
+    assert_eq!(call("take_right"), out_of_bounds);
+}
+
+#[cfg(feature = "jvm")]
+#[test]
+fn test_catch_panic() {
+    let runner = TestRunner::new();
+
+    let wat = r#"
+    (module
+        (import "env0" "test_panic" (func $test_panic))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (call $test_panic)
+            (i32.const 0)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 0))
+    )
+    "#;
+
+    let result = crate::catch_panic(Some(&CONTRACT_ID), || match runner.run(wat, vec![]) {
+        Ok(result) => result[0].i32().unwrap_or_default(),
+        Err(error) => error.as_i32(),
+    });
+    assert_eq!(result, ExecutableError::Panic(String::new()).as_i32());
+
+    let result = crate::catch_panic(None, || 0);
+    assert_eq!(result, 0);
+}
+
+#[test]
+fn test_allocator() {
+    let mut runner = TestRunner::new();
+
+    // The contract allocator is used when exported
+    {
+        let wat = r#"
+        (module
+            (import "env0" "sha256" (func $sha256 (param i32 i32) (result i32 i32 i32)))
+
+            (import "env" "memory" (memory 1 1))
+
+            (global $next (mut i32) (i32.const 1024))
+
+            (func (export "allocate") (param $size i32) (result i32)
+                (global.get $next)
+                (global.set $next (i32.add (global.get $next) (local.get $size)))
+            )
+
+            (func (export "_constructor") (result i32)
+                (local $error i32) (local $offset i32) (local $length i32)
+                (call $sha256
+                    (i32.const 0)
+                    (i32.const 3))
+                (local.set $length)
+                (local.set $offset)
+                (local.tee $error)
+                (if (then (return (local.get $error))))
+
+                (i32.add (local.get $offset) (global.get $next))
+            )
+
+            (global $__heap_base (export "__heap_base") i32 (i32.const 3))
+
+            (data (i32.const 0) "key")
+        )
+        "#;
+
+        let result = runner.run(wat, vec![]).expect("Call failed");
+        assert_eq!(result[0].i32(), Some(1024 + 1024 + 32));
+    }
+
+    // The contract allocator fails
+    {
+        let wat = r#"
+        (module
+            (import "env0" "sha256" (func $sha256 (param i32 i32) (result i32 i32 i32)))
+
+            (import "env" "memory" (memory 1 1))
+
+            (func (export "allocate") (param $size i32) (result i32)
+                (unreachable)
+            )
+
+            (func (export "_constructor") (result i32)
+                (call $sha256
+                    (i32.const 0)
+                    (i32.const 3))
+                (drop)
+                (drop)
+            )
+
+            (global $__heap_base (export "__heap_base") i32 (i32.const 3))
+
+            (data (i32.const 0) "key")
+        )
+        "#;
+
+        let result = runner.run(wat, vec![]).expect("Call failed");
+        assert_eq!(
+            result[0].i32(),
+            Some(RuntimeError::AllocationFailed(String::new()).as_i32())
+        );
     }
+
+    // Memory is grown when the heap address reaches its end
+    {
+        let wat = r#"
+        (module
+            (import "env0" "sha256" (func $sha256 (param i32 i32) (result i32 i32 i32)))
+
+            (import "env" "memory" (memory 1))
+
+            (func (export "_constructor") (result i32)
+                (local $error i32)
+                (call $sha256
+                    (i32.const 0)
+                    (i32.const 3))
+                (drop)
+                (drop)
+                (local.tee $error)
+                (if (then (return (local.get $error))))
+
+                (memory.size)
+            )
+
+            (global $__heap_base (export "__heap_base") i32 (i32.const 65520))
+
+            (data (i32.const 0) "key")
+        )
+        "#;
+
+        runner.config.memory = (1, 2);
+
+        let result = runner.run(wat, vec![]).expect("Call failed");
+        assert_eq!(result[0].i32(), Some(2));
+    }
+}
+
+#[test]
+fn test_reentrant_call() {
+    let runner = TestRunner::new();
+
+    let wat = r#"
+    (module
+        (import "env0" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 32)
+                (i32.const 5))
+        )
+
+        (func (export "inner") (result i32)
+            (i32.const 0)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 37))
+
+        (data (i32.const 0) "\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07")
+        (data (i32.const 32) "inner")
+    )
+    "#;
+
+    // The contract calls itself, so both frames are executed by the same cached module
+    runner.ledger.set_bytecode(
+        &CONTRACT_ID,
+        wat2wasm(wat).expect("WAT code parsing failed"),
+    );
+
+    for _ in 0..2 {
+        let result = runner.run(wat, vec![]).expect("Call failed");
+        assert_eq!(result[0].i32(), Some(0));
+    }
+}
+
+const RETURN_CONTRACT_ID: [u8; 32] = [9; 32];
+
+const RETURN_CONTRACT: &str = r#"
+(module
+    (import "env2" "set_return" (func $set_return (param i32 i32) (result i32)))
+
+    (import "env" "memory" (memory 1 1))
+
+    (func (export "_constructor") (result i32)
+        (i32.const 0)
+    )
+
+    (func (export "greet") (result i32)
+        (call $set_return
+            (i32.const 0)
+            (i32.const 2))
+    )
+
+    (func (export "silent") (result i32)
+        (i32.const 0)
+    )
+
+    (global $__heap_base (export "__heap_base") i32 (i32.const 2))
+
+    (data (i32.const 0) "Hi")
+)
+"#;
+
+#[test]
+fn test_return_data() {
+    let runner = TestRunner::new();
+
+    runner.ledger.set_bytecode(
+        &RETURN_CONTRACT_ID,
+        wat2wasm(RETURN_CONTRACT).expect("WAT code parsing failed"),
+    );
+
+    let wat = r#"
+    (module
+        (import "env0" "test_memory" (func $test_memory (param i32 i32)))
+        (import "env2" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32 i32 i32)))
+        (import "env2" "set_return" (func $set_return (param i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (local $error i32) (local $offset i32) (local $length i32)
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 32)
+                (i32.const 5))
+            (local.set $length)
+            (local.set $offset)
+            (local.tee $error)
+            (if (then (return (local.get $error))))
+
+            (call $test_memory
+                (local.get $offset)
+                (local.get $length))
+
+            (call $set_return
+                (local.get $offset)
+                (local.get $length))
+        )
+
+        (func (export "silent") (result i32)
+            (drop (call $set_return
+                (i32.const 32)
+                (i32.const 5)))
+
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 37)
+                (i32.const 6))
+            (drop)
+            (drop)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 43))
+
+        (data (i32.const 0) "\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09")
+        (data (i32.const 32) "greet")
+        (data (i32.const 37) "silent")
+    )
+    "#;
+
+    let mut vm = runner.vm(wat);
+    let result = vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+    assert_eq!(vm.return_data(), b"Hi");
+
+    // The callee that returned nothing does not see or replace the data of the caller
+    let mut vm = runner.vm(wat);
+    let result = vm.run("silent", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+    assert_eq!(vm.return_data(), b"greet");
+}
+
+#[test]
+fn test_abi_results() {
+    let runner = TestRunner::new();
+
+    let contract = |results: Vec<u8>| {
+        let abi = Abi::new(vec![Function {
+            name: "_constructor".to_string(),
+            params: vec![],
+            results,
+        }]);
+        let section: String = abi
+            .as_bytes()
+            .iter()
+            .map(|byte| format!("\\{:02x}", byte))
+            .collect();
+
+        format!(
+            r#"
+            (module
+                (@custom "{}" "{}")
+                (import "env2" "set_return" (func $set_return (param i32 i32) (result i32)))
+
+                (import "env" "memory" (memory 1 1))
+
+                (func (export "_constructor") (result i32)
+                    (call $set_return
+                        (i32.const 0)
+                        (i32.const 13))
+                )
+
+                (global $__heap_base (export "__heap_base") i32 (i32.const 13))
+
+                ;; A list of the integer 42
+                (data (i32.const 0) "\00\01\00\00\00\00\00\00\00\00\00\00\2a")
+            )
+            "#,
+            SECTION_NAME, section
+        )
+    };
+
+    let result = runner
+        .run(&contract(vec![TAG_INTEGER]), vec![])
+        .expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    let result = runner.run(&contract(vec![TAG_STRING]), vec![]);
+    assert!(matches!(
+        result,
+        Err(Error::Executable(ExecutableError::InvalidAbi(_)))
+    ));
+
+    let result = runner.run(&contract(vec![]), vec![]);
+    assert!(matches!(
+        result,
+        Err(Error::Executable(ExecutableError::InvalidAbi(_)))
+    ));
+}
+
+const JOURNAL_CONTRACT_ID: [u8; 32] = [10; 32];
+
+const JOURNAL_CONTRACT: &str = r#"
+(module
+    (import "env0" "set_storage_int" (func $set_storage_int (param i32 i32 i64) (result i32)))
+    (import "env0" "transfer" (func $transfer (param i32 i32 i32 i32 i64) (result i32)))
+
+    (import "env" "memory" (memory 1 1))
+
+    (func (export "_constructor") (result i32)
+        (i32.const 0)
+    )
+
+    (func (export "fail") (result i32)
+        (drop (call $set_storage_int
+            (i32.const 26)
+            (i32.const 3)
+            (i64.const 1)))
+        (drop (call $transfer
+            (i32.const 0)
+            (i32.const 0)
+            (i32.const 0)
+            (i32.const 26)
+            (i64.const 300)))
+        (i32.const 1)
+    )
+
+    (func (export "ok") (result i32)
+        (call $set_storage_int
+            (i32.const 26)
+            (i32.const 3)
+            (i64.const 2))
+    )
+
+    (global $__heap_base (export "__heap_base") i32 (i32.const 29))
+
+    (data (i32.const 0) "\01\54\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa")
+    (data (i32.const 26) "key")
+)
+"#;
+
+#[test]
+fn test_journal_rollback() {
+    let runner = TestRunner::new();
+
+    runner.ledger.set_bytecode(
+        &JOURNAL_CONTRACT_ID,
+        wat2wasm(JOURNAL_CONTRACT).expect("WAT code parsing failed"),
+    );
+    runner.ledger.set_balance(&[], &JOURNAL_CONTRACT_ID, 1000);
+
+    let wat = r#"
+    (module
+        (import "env0" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32)))
+        (import "env0" "set_storage_int" (func $set_storage_int (param i32 i32 i64) (result i32)))
+        (import "env0" "get_storage_int" (func $get_storage_int (param i32 i32 i32 i32) (result i32 i64)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (local $error i32) (local $value i64)
+            (drop (call $set_storage_int
+                (i32.const 41)
+                (i32.const 3)
+                (i64.const 7)))
+
+            ;; The failed call leaves no changes
+            (if (i32.eqz (call $call_contract
+                    (i32.const 0)
+                    (i32.const 32)
+                    (i32.const 32)
+                    (i32.const 4)))
+                (then (return (i32.const 100))))
+
+            (local.tee $error (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 36)
+                (i32.const 2)))
+            (if (then (return (local.get $error))))
+
+            ;; Changes of the successful call are visible before they reach the node
+            (call $get_storage_int
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 38)
+                (i32.const 3))
+            (local.set $value)
+            (local.tee $error)
+            (if (then (return (local.get $error))))
+
+            (if (i64.ne (local.get $value) (i64.const 2))
+                (then (return (i32.const 101))))
+
+            (i32.const 0)
+        )
+
+        (func (export "abort") (result i32)
+            (drop (call $set_storage_int
+                (i32.const 41)
+                (i32.const 3)
+                (i64.const 8)))
+            (i32.const 1)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 44))
+
+        (data (i32.const 0) "\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a")
+        (data (i32.const 32) "fail")
+        (data (i32.const 36) "ok")
+        (data (i32.const 38) "key")
+        (data (i32.const 41) "own")
+    )
+    "#;
+
+    let result = runner.run(wat, vec![]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    assert_eq!(
+        runner.ledger.storage(&CONTRACT_ID, b"own"),
+        Some(DataEntry::Integer(7))
+    );
+    assert_eq!(
+        runner.ledger.storage(&JOURNAL_CONTRACT_ID, b"key"),
+        Some(DataEntry::Integer(2))
+    );
+    assert_eq!(runner.ledger.balance(&[], &JOURNAL_CONTRACT_ID), 1000);
+
+    // Nothing reaches the node if the top-level call fails
+    let result = runner.call(wat, "abort", vec![]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(1));
+    assert_eq!(
+        runner.ledger.storage(&CONTRACT_ID, b"own"),
+        Some(DataEntry::Integer(7))
+    );
+}
+
+#[test]
+fn test_execution_result() {
+    let runner = TestRunner::new();
+
+    runner.ledger.set_bytecode(
+        &JOURNAL_CONTRACT_ID,
+        wat2wasm(JOURNAL_CONTRACT).expect("WAT code parsing failed"),
+    );
+
+    let wat = r#"
+    (module
+        (import "env0" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32)))
+        (import "env0" "set_storage_int" (func $set_storage_int (param i32 i32 i64) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (drop (call $set_storage_int
+                (i32.const 34)
+                (i32.const 3)
+                (i64.const 7)))
+
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 32)
+                (i32.const 2))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 37))
+
+        (data (i32.const 0) "\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a")
+        (data (i32.const 32) "ok")
+        (data (i32.const 34) "own")
+    )
+    "#;
+
+    let mut vm = runner.vm(wat);
+    let result = vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    let result = vm.execution_result().expect("Error execution result");
+
+    let storage: Vec<_> = result
+        .storage
+        .iter()
+        .map(|item| {
+            (
+                item.contract_id.clone(),
+                item.key.clone(),
+                item.value.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        storage,
+        vec![
+            (CONTRACT_ID.to_vec(), b"own".to_vec(), DataEntry::Integer(7)),
+            (
+                JOURNAL_CONTRACT_ID.to_vec(),
+                b"key".to_vec(),
+                DataEntry::Integer(2)
+            ),
+        ]
+    );
+    assert!(result.transfers.is_empty());
+
+    assert_eq!(result.fuel_consumed, vm.fuel_consumed());
+    assert_eq!(result.calls.len(), 2);
+    assert_eq!(result.calls[1].contract_id, JOURNAL_CONTRACT_ID.to_vec());
+    assert_eq!(result.calls[1].depth, 1);
+}
+
+#[test]
+fn test_rollback_issue_lease() {
+    let runner = TestRunner::new();
+
+    let callee_id = [12; 32];
+    runner.ledger.set_bytecode(
+        &callee_id,
+        wat2wasm(
+            r#"
+            (module
+                (import "env0" "issue" (func $issue (param i32 i32 i32 i32 i64 i32 i32) (result i32 i32 i32)))
+                (import "env0" "lease_address" (func $lease_address (param i32 i32 i64) (result i32 i32 i32)))
+
+                (import "env" "memory" (memory 1 1))
+
+                (func (export "_constructor") (result i32)
+                    (i32.const 0)
+                )
+
+                (func (export "issue") (result i32)
+                    (drop (drop (drop (call $issue
+                        (i32.const 26)
+                        (i32.const 5)
+                        (i32.const 26)
+                        (i32.const 0)
+                        (i64.const 1000)
+                        (i32.const 0)
+                        (i32.const 0)))))
+                    (i32.const 1)
+                )
+
+                (func (export "lease") (result i32)
+                    (drop (drop (drop (call $lease_address
+                        (i32.const 0)
+                        (i32.const 26)
+                        (i64.const 10)))))
+                    (i32.const 1)
+                )
+
+                (global $__heap_base (export "__heap_base") i32 (i32.const 31))
+
+                (data (i32.const 0) "\01\54\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa\aa")
+                (data (i32.const 26) "Token")
+            )
+            "#,
+        )
+        .expect("WAT code parsing failed"),
+    );
+
+    let wat = r#"
+    (module
+        (import "env0" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            ;; Both calls fail after the node applied the operation
+            (drop (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 32)
+                (i32.const 5)))
+            (drop (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 37)
+                (i32.const 5)))
+            (i32.const 0)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 42))
+
+        (data (i32.const 0) "\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c\0c")
+        (data (i32.const 32) "issue")
+        (data (i32.const 37) "lease")
+    )
+    "#;
+
+    let mut vm = runner.vm(wat);
+    let result = vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    let result = vm.execution_result().expect("Error execution result");
+
+    // The issue is compensated by burning the issued quantity
+    assert_eq!(result.issues.len(), 1);
+    let asset_id = result.issues[0].asset_id.clone();
+    assert_eq!(result.burns.len(), 1);
+    assert_eq!(result.burns[0].asset_id, asset_id);
+    assert_eq!(result.burns[0].amount, 1000);
+    assert_eq!(runner.ledger.balance(&asset_id, &callee_id), 0);
+
+    // The lease is compensated by canceling it
+    assert_eq!(result.leases.len(), 1);
+    let lease_id = result.leases[0].lease_id.clone();
+    assert_eq!(result.cancel_leases.len(), 1);
+    assert_eq!(result.cancel_leases[0].lease_id, lease_id);
+    assert_eq!(runner.ledger.lease_info(&lease_id), None);
+}
+
+#[test]
+fn test_read_only() {
+    let runner = TestRunner::new();
+
+    runner.ledger.set_bytecode(
+        &JOURNAL_CONTRACT_ID,
+        wat2wasm(JOURNAL_CONTRACT).expect("WAT code parsing failed"),
+    );
+    runner
+        .ledger
+        .set_storage_entry(&CONTRACT_ID, b"key", DataEntry::Integer(42));
+
+    let wat = r#"
+    (module
+        (import "env0" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32)))
+        (import "env0" "set_storage_int" (func $set_storage_int (param i32 i32 i64) (result i32)))
+        (import "env0" "get_storage_int" (func $get_storage_int (param i32 i32 i32 i32) (result i32 i64)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (call $set_storage_int
+                (i32.const 34)
+                (i32.const 3)
+                (i64.const 7))
+        )
+
+        (func (export "get") (result i32)
+            (local $error i32) (local $value i64)
+            (call $get_storage_int
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 34)
+                (i32.const 3))
+            (local.set $value)
+            (local.tee $error)
+            (if (then (return (local.get $error))))
+
+            (i32.wrap_i64 (i64.sub (local.get $value) (i64.const 42)))
+        )
+
+        (func (export "nested") (result i32)
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 32)
+                (i32.const 2))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 37))
+
+        (data (i32.const 0) "\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a\0a")
+        (data (i32.const 32) "ok")
+        (data (i32.const 34) "key")
+    )
+    "#;
+
+    for func_name in ["_constructor", "nested"] {
+        let mut vm = runner.vm(wat);
+        vm.set_read_only(true);
+
+        let result = vm.run(func_name, &[]).expect("Call failed");
+        assert_eq!(
+            result[0].i32(),
+            Some(RuntimeError::ReadOnlyViolation.as_i32())
+        );
+    }
+
+    assert_eq!(
+        runner.ledger.storage(&CONTRACT_ID, b"key"),
+        Some(DataEntry::Integer(42))
+    );
+    assert_eq!(runner.ledger.storage(&JOURNAL_CONTRACT_ID, b"key"), None);
+
+    // Reading the state is allowed
+    let mut vm = runner.vm(wat);
+    vm.set_read_only(true);
+
+    let result = vm.run("get", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+}
+
+#[test]
+fn test_delete_storage() {
+    let runner = TestRunner::new();
+
+    runner
+        .ledger
+        .set_storage_entry(&CONTRACT_ID, b"key", DataEntry::Integer(42));
+
+    let wat = r#"
+    (module
+        (import "env0" "set_storage_int" (func $set_storage_int (param i32 i32 i64) (result i32)))
+        (import "env0" "contains_key" (func $contains_key (param i32 i32 i32 i32) (result i32 i32)))
+        (import "env3" "delete_storage" (func $delete_storage (param i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func $contains (param $offset i32) (result i32)
+            (local $error i32) (local $result i32)
+            (call $contains_key
+                (i32.const 0)
+                (i32.const 0)
+                (local.get $offset)
+                (i32.const 3))
+            (local.set $result)
+            (local.tee $error)
+            (if (then (return (i32.const 1))))
+            (local.get $result)
+        )
+
+        (func (export "_constructor") (result i32)
+            (drop (call $set_storage_int
+                (i32.const 3)
+                (i32.const 3)
+                (i64.const 1)))
+            (drop (call $delete_storage
+                (i32.const 3)
+                (i32.const 3)))
+            (if (call $contains (i32.const 3))
+                (then (return (i32.const 100))))
+
+            (drop (call $delete_storage
+                (i32.const 0)
+                (i32.const 3)))
+            (if (call $contains (i32.const 0))
+                (then (return (i32.const 101))))
+
+            (i32.const 0)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 6))
+
+        (data (i32.const 0) "key")
+        (data (i32.const 3) "tmp")
+    )
+    "#;
+
+    let mut vm = runner.vm(wat);
+    let result = vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    assert_eq!(runner.ledger.storage(&CONTRACT_ID, b"key"), None);
+    assert_eq!(runner.ledger.storage(&CONTRACT_ID, b"tmp"), None);
+
+    let result = vm.execution_result().expect("Error execution result");
+    let storage: Vec<_> = result
+        .storage
+        .iter()
+        .map(|item| (item.key.clone(), item.value.clone()))
+        .collect();
+    assert_eq!(
+        storage,
+        vec![
+            (b"tmp".to_vec(), DataEntry::Integer(1)),
+            (b"tmp".to_vec(), DataEntry::Deleted),
+            (b"key".to_vec(), DataEntry::Deleted),
+        ]
+    );
+}
+
+#[test]
+fn test_set_storage_batch() {
+    let runner = TestRunner::new();
+
+    let wat = r#"
+    (module
+        (import "env0" "get_storage_int" (func $get_storage_int (param i32 i32 i32 i32) (result i32 i64)))
+        (import "env3" "set_storage_batch" (func $set_storage_batch (param i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (local $error i32) (local $value i64)
+            (local.tee $error (call $set_storage_batch
+                (i32.const 0)
+                (i32.const 19)))
+            (if (then (return (local.get $error))))
+
+            (call $get_storage_int
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 4)
+                (i32.const 1))
+            (local.set $value)
+            (local.tee $error)
+            (if (then (return (local.get $error))))
+
+            (i32.wrap_i64 (i64.sub (local.get $value) (i64.const 1)))
+        )
+
+        (func (export "invalid") (result i32)
+            (call $set_storage_batch
+                (i32.const 0)
+                (i32.const 18))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 19))
+
+        (data (i32.const 0) "\00\02\00\01a\00\00\00\00\00\00\00\00\01\00\01b\01\01")
+    )
+    "#;
+
+    let result = runner.call(wat, "invalid", vec![]).expect("Call failed");
+    assert_eq!(
+        result[0].i32(),
+        Some(ExecutableError::FailedDeserialize.as_i32())
+    );
+    assert_eq!(runner.ledger.storage(&CONTRACT_ID, b"a"), None);
+
+    let result = runner.run(wat, vec![]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+    assert_eq!(
+        runner.ledger.storage(&CONTRACT_ID, b"a"),
+        Some(DataEntry::Integer(1))
+    );
+    assert_eq!(
+        runner.ledger.storage(&CONTRACT_ID, b"b"),
+        Some(DataEntry::Boolean(1))
+    );
+}
+
+#[test]
+fn test_get_storage() {
+    let runner = TestRunner::new();
+
+    runner
+        .ledger
+        .set_storage_entry(&CONTRACT_ID, b"str", DataEntry::String(b"Hi".to_vec()));
+    runner
+        .ledger
+        .set_storage_entry(&CONTRACT_ID, b"int", DataEntry::Integer(5));
+
+    let wat = r#"
+    (module
+        (import "env0" "test_memory" (func $test_memory (param i32 i32)))
+        (import "env3" "get_storage" (func $get_storage (param i32 i32 i32 i32) (result i32 i32 i32 i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (local $error i32) (local $tag i32) (local $offset i32) (local $length i32)
+            (call $get_storage
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 3))
+            (local.set $length)
+            (local.set $offset)
+            (local.set $tag)
+            (local.tee $error)
+            (if (then (return (local.get $error))))
+
+            (if (i32.ne (local.get $tag) (i32.const 3))
+                (then (return (i32.const 100))))
+            (call $test_memory
+                (local.get $offset)
+                (local.get $length))
+
+            (call $get_storage
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 3)
+                (i32.const 3))
+            (local.set $length)
+            (local.set $offset)
+            (local.set $tag)
+            (local.tee $error)
+            (if (then (return (local.get $error))))
+
+            (if (i32.ne (local.get $tag) (i32.const 0))
+                (then (return (i32.const 101))))
+            (if (i32.ne (local.get $length) (i32.const 8))
+                (then (return (i32.const 102))))
+
+            ;; The key is not found
+            (call $get_storage
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 6)
+                (i32.const 7))
+            (drop)
+            (drop)
+            (local.set $tag)
+            (local.tee $error)
+            (if (then (return (local.get $error))))
+
+            (if (i32.ne (local.get $tag) (i32.const -1))
+                (then (return (i32.const 103))))
+
+            (i32.const 0)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 13))
+
+        (data (i32.const 0) "str")
+        (data (i32.const 3) "int")
+        (data (i32.const 6) "missing")
+    )
+    "#;
+
+    let result = runner.run(wat, vec![]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+}
+
+#[test]
+fn test_storage_keys() {
+    let mut runner = TestRunner::new();
+
+    for key in ["item:1", "item:2", "item:3", "other"] {
+        runner
+            .ledger
+            .set_storage_entry(&CONTRACT_ID, key.as_bytes(), DataEntry::Integer(0));
+    }
+
+    let wat = r#"
+    (module
+        (import "env0" "set_storage_int" (func $set_storage_int (param i32 i32 i64) (result i32)))
+        (import "env2" "set_return" (func $set_return (param i32 i32) (result i32)))
+        (import "env3" "delete_storage" (func $delete_storage (param i32 i32) (result i32)))
+        (import "env3" "storage_keys" (func $storage_keys (param i32 i32 i32 i32 i32 i32 i32) (result i32 i32 i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func $keys (param $offset_cursor i32) (param $length_cursor i32) (param $limit i32) (result i32)
+            (local $error i32) (local $offset i32) (local $length i32)
+            (drop (call $set_storage_int
+                (i32.const 5)
+                (i32.const 6)
+                (i64.const 4)))
+            (drop (call $delete_storage
+                (i32.const 11)
+                (i32.const 6)))
+
+            (call $storage_keys
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 5)
+                (local.get $offset_cursor)
+                (local.get $length_cursor)
+                (local.get $limit))
+            (local.set $length)
+            (local.set $offset)
+            (local.tee $error)
+            (if (then (return (local.get $error))))
+
+            (call $set_return
+                (local.get $offset)
+                (local.get $length))
+        )
+
+        (func (export "_constructor") (result i32)
+            (call $keys (i32.const 0) (i32.const 0) (i32.const 2))
+        )
+
+        (func (export "next") (result i32)
+            (call $keys (i32.const 17) (i32.const 6) (i32.const 10))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 23))
+
+        (data (i32.const 0) "item:")
+        (data (i32.const 5) "item:4")
+        (data (i32.const 11) "item:2")
+        (data (i32.const 17) "item:3")
+    )
+    "#;
+
+    let keys = |func_name: &str| {
+        let mut vm = runner.vm(wat);
+        let result = vm.run(func_name, &[]).expect("Call failed");
+        assert_eq!(result[0].i32(), Some(0));
+
+        crate::runtime::utils::get_list(vm.return_data(), &mut 0).expect("Error get list")
+    };
+
+    // Buffered writes and deletions are taken into account
+    assert_eq!(
+        keys("_constructor"),
+        vec![b"item:1".to_vec(), b"item:3".to_vec()]
+    );
+    assert_eq!(keys("next"), vec![b"item:4".to_vec()]);
+
+    // Fuel is charged for the requested keys before the query, a call that cannot pay traps
+    runner
+        .cost_schedule
+        .set("env3", "storage_keys", Cost::new(0, 0).with_per_entry(200));
+    let result = runner.run(wat, vec![]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    let result = runner.call(wat, "next", vec![]);
+    assert!(matches!(
+        result,
+        Err(Error::Executable(ExecutableError::FailedExec(_)))
+    ));
+}
+
+#[test]
+fn test_extended_types() {
+    let runner = TestRunner::new();
+
+    let wat = r#"
+    (module
+        (import "env2" "set_return" (func $set_return (param i32 i32) (result i32)))
+        (import "env4" "get_storage_list" (func $get_storage_list (param i32 i32 i32 i32) (result i32 i32 i32)))
+        (import "env4" "set_storage_uint" (func $set_storage_uint (param i32 i32 i64) (result i32)))
+        (import "env4" "set_storage_list" (func $set_storage_list (param i32 i32 i32 i32) (result i32)))
+        (import "env4" "set_storage_int128" (func $set_storage_int128 (param i32 i32 i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (local $error i32) (local $offset i32) (local $length i32)
+            (local.tee $error (call $set_storage_uint
+                (i32.const 1)
+                (i32.const 1)
+                (i64.const -1)))
+            (if (then (return (local.get $error))))
+
+            (local.tee $error (call $set_storage_list
+                (i32.const 0)
+                (i32.const 1)
+                (i32.const 2)
+                (i32.const 13)))
+            (if (then (return (local.get $error))))
+
+            (call $get_storage_list
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 0)
+                (i32.const 1))
+            (local.set $length)
+            (local.set $offset)
+            (local.tee $error)
+            (if (then (return (local.get $error))))
+
+            (call $set_return
+                (local.get $offset)
+                (local.get $length))
+        )
+
+        (func (export "invalid") (result i32)
+            (call $set_storage_int128
+                (i32.const 0)
+                (i32.const 1)
+                (i32.const 2)
+                (i32.const 13))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 15))
+
+        (data (i32.const 0) "lu\00\02\00\00\00\00\00\00\00\00\07\01\01")
+    )
+    "#;
+
+    let result = runner.call(wat, "invalid", vec![]).expect("Call failed");
+    assert_eq!(
+        result[0].i32(),
+        Some(ExecutableError::FailedDeserialize.as_i32())
+    );
+
+    let mut vm = runner.vm(wat);
+    let result = vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    let list = DataEntry::List(vec![DataEntry::Integer(7), DataEntry::Boolean(1)]);
+    assert_eq!(vm.return_data(), list.clone().into_tagged().1);
+    assert_eq!(runner.ledger.storage(&CONTRACT_ID, b"l"), Some(list));
+    assert_eq!(
+        runner.ledger.storage(&CONTRACT_ID, b"u"),
+        Some(DataEntry::UnsignedInteger(u64::MAX))
+    );
+}
+
+#[test]
+fn test_abi() {
+    let runner = TestRunner::new();
+
+    // transfer(amount: Integer)
+    let wat = r#"
+    (module
+        (@custom "wevm:abi" "\00\01\00\08transfer\00\01\00\06amount\00\00\00")
+
+        (func (export "_constructor") (result i32)
+            (i32.const 0)
+        )
+
+        (func (export "transfer") (param $amount i64) (result i32)
+            (i32.wrap_i64 (local.get $amount))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 0))
+    )
+    "#;
+
+    let mut params = vec![0, 1];
+    params.extend(DataEntry::Integer(7).serialize(None));
+    let result = runner.call(wat, "transfer", params).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(7));
+
+    let mut params = vec![0, 1];
+    params.extend(DataEntry::Boolean(1).serialize(None));
+    let result = runner.call(wat, "transfer", params);
+    assert_eq!(
+        result.unwrap_err(),
+        Error::Executable(ExecutableError::FailedParseFuncArgs(
+            "Argument 0 (amount): expected type 0, found 1".to_string()
+        ))
+    );
+
+    let result = runner.call(wat, "transfer", vec![]);
+    assert_eq!(
+        result.unwrap_err(),
+        Error::Executable(ExecutableError::FailedParseFuncArgs(
+            "Function transfer takes 1 arguments, given 0".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_vm_config() {
+    let mut runner = TestRunner::new();
+
+    let wat = r#"
+    (module
+        (import "env0" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (i32.const 0)
+        )
+
+        (func (export "inner") (result i32)
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 32)
+                (i32.const 5))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 37))
+
+        (data (i32.const 0) "\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07")
+        (data (i32.const 32) "inner")
+    )
+    "#;
+
+    // The contract calls itself until the call depth is exceeded,
+    // the error code is returned to the callers
+    runner.ledger.set_bytecode(
+        &CONTRACT_ID,
+        wat2wasm(wat).expect("WAT code parsing failed"),
+    );
+    runner.config.max_call_depth = 3;
+
+    let result = runner.call(wat, "inner", vec![]).expect("Call failed");
+    assert_eq!(
+        result[0].i32(),
+        Some(ExecutableError::StackOverflow.as_i32())
+    );
+
+    let bytecode_size = wat2wasm(wat).expect("WAT code parsing failed").len();
+    runner.config.max_bytecode_size = bytecode_size - 1;
+
+    let result = runner.run(wat, vec![]);
+    assert_eq!(
+        result.unwrap_err(),
+        Error::Executable(ExecutableError::BytecodeSizeLimit(format!(
+            "Bytecode size {} exceeds the limit {}",
+            bytecode_size,
+            bytecode_size - 1
+        )))
+    );
+
+    runner.config.max_bytecode_size = bytecode_size;
+    runner.config.max_params_size = 1;
+
+    let result = runner.run(wat, vec![0, 0]);
+    assert_eq!(
+        result.unwrap_err(),
+        Error::Executable(ExecutableError::ParamsSizeLimit(
+            "Arguments size 2 exceeds the limit 1".to_string()
+        ))
+    );
+
+    runner.config.memory = (2, 1);
+    let result = Vm::new(
+        CONTRACT_ID.to_vec(),
+        vec![],
+        runner.config,
+        0,
+        vec![],
+        CostSchedule::default(),
+        runner.ledger.clone(),
+    );
+    assert!(matches!(
+        result,
+        Err(Error::Executable(ExecutableError::InvalidConfig(_)))
+    ));
+}
+
+#[test]
+fn test_timeout() {
+    let mut runner = TestRunner::new();
+
+    let wat = r#"
+    (module
+        (import "env0" "test_sleep" (func $test_sleep (param i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (i32.const 0)
+        )
+
+        (func (export "sleep") (result i32)
+            (call $test_sleep (i32.const 50))
+            (call $test_sleep (i32.const 0))
+            (i32.const 0)
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 0))
+    )
+    "#;
+
+    let result = runner.call(wat, "sleep", vec![]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(0));
+
+    runner.config.timeout = Some(std::time::Duration::from_millis(10));
+
+    // The host function called after the deadline consumes the remaining fuel
+    let mut vm = runner.vm(wat);
+    let result = vm.run("sleep", &[]);
+    assert_eq!(
+        result.unwrap_err(),
+        Error::Executable(ExecutableError::Timeout)
+    );
+    assert_eq!(vm.fuel(), 0);
+
+    runner.config.timeout = Some(std::time::Duration::ZERO);
+
+    let result = runner.run(wat, vec![]);
+    assert_eq!(
+        result.unwrap_err(),
+        Error::Executable(ExecutableError::Timeout)
+    );
+}
+
+#[test]
+fn test_error_report() {
+    let runner = TestRunner::new();
+
+    let failing_id = [11; 32];
+    runner.ledger.set_bytecode(
+        &failing_id,
+        wat2wasm(
+            r#"
+            (module
+                (import "env0" "require" (func $require (param i32 i32) (result i32)))
+
+                (import "env" "memory" (memory 1 1))
+
+                (func (export "_constructor") (result i32)
+                    (i32.const 0)
+                )
+
+                (func (export "trap") (result i32)
+                    (unreachable)
+                )
+
+                (func (export "check") (result i32)
+                    (drop (call $require
+                        (i32.const 0)
+                        (i32.const 10)))
+                    (i32.const 7)
+                )
+
+                (global $__heap_base (export "__heap_base") i32 (i32.const 10))
+
+                (data (i32.const 0) "Not enough")
+            )
+            "#,
+        )
+        .expect("WAT code parsing failed"),
+    );
+
+    let wat = r#"
+    (module
+        (import "env0" "call_contract" (func $call_contract (param i32 i32 i32 i32) (result i32)))
+
+        (import "env" "memory" (memory 1 1))
+
+        (func (export "_constructor") (result i32)
+            (i32.const 0)
+        )
+
+        (func (export "trap") (result i32)
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 32)
+                (i32.const 4))
+        )
+
+        (func (export "check") (result i32)
+            (call $call_contract
+                (i32.const 0)
+                (i32.const 32)
+                (i32.const 36)
+                (i32.const 5))
+        )
+
+        (global $__heap_base (export "__heap_base") i32 (i32.const 41))
+
+        (data (i32.const 0) "\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b\0b")
+        (data (i32.const 32) "trap")
+        (data (i32.const 36) "check")
+    )
+    "#;
+
+    let frames = |func_name: &str| {
+        vec![
+            ErrorFrame {
+                contract_id: CONTRACT_ID.to_vec(),
+                func_name: func_name.to_string(),
+            },
+            ErrorFrame {
+                contract_id: failing_id.to_vec(),
+                func_name: func_name.to_string(),
+            },
+        ]
+    };
+
+    // The error of the callee is passed to the caller as the error code
+    let mut vm = runner.vm(wat);
+    let result = vm.run("trap", &[]).expect("Call failed");
+    let code = ExecutableError::FailedExec(String::new()).as_i32();
+    assert_eq!(result[0].i32(), Some(code));
+
+    let report = vm.error_report().expect("Error not reported");
+    assert_eq!(report.code, code);
+    assert_eq!(report.category, ErrorCategory::Executable);
+    assert!(report.message.contains("Reason"));
+    assert_eq!(report.frames, frames("trap"));
+    assert_eq!(report.contract_id(), failing_id);
+
+    // The error code returned by the contract is reported with the message of `require`
+    let mut vm = runner.vm(wat);
+    let result = vm.run("check", &[]).expect("Call failed");
+    assert_eq!(result[0].i32(), Some(7));
+    assert_eq!(
+        vm.error_report(),
+        Some(&ErrorReport::contract(
+            7,
+            "Not enough".to_string(),
+            frames("check")
+        ))
+    );
+
+    let mut vm = runner.vm(wat);
+    vm.run("_constructor", &[]).expect("Call failed");
+    assert_eq!(vm.error_report(), None);
+
+    let mut vm = runner.vm(wat);
+    let result = vm.run("missing", &[]);
+    assert_eq!(
+        vm.error_report(),
+        Some(&ErrorReport::new(
+            &result.unwrap_err(),
+            vec![ErrorFrame {
+                contract_id: CONTRACT_ID.to_vec(),
+                func_name: "missing".to_string(),
+            }]
+        ))
+    );
 }
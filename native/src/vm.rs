@@ -1,22 +1,39 @@
+pub mod config;
+pub mod cost_schedule;
+pub mod error_report;
+pub mod execution_result;
+pub mod journal;
+
 use crate::{
-    error::{Error, ExecutableError, Result},
+    error::{Error, ExecutableError, Result, RuntimeError},
     exec::{Executable, LoadableFunction},
     modules::Module,
-    runtime::payment_id::PaymentId,
+    node::Node,
+    runtime::{data_entry::DataEntry, payment_id::PaymentId},
 };
 use base58::ToBase58;
-use jni::{objects::GlobalRef, JavaVM};
+use config::VmConfig;
+use cost_schedule::CostSchedule;
+use error_report::{ErrorFrame, ErrorReport};
+use execution_result::ExecutionResult;
+use journal::{BalanceKey, Journal, Operation};
 use log::debug;
-use std::str::FromStr;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    str::FromStr,
+    time::Instant,
+};
 use wasmi::Value;
 
-const MAX_FRAMES: usize = 64;
-
 /// A frame of the call stack that stores the `contract_id` and `bytecode` of the contract.
 pub struct Frame {
     contract_id: Vec<u8>,
     bytecode: Vec<u8>,
+    func_name: String,
     nonce: u64,
+    /// Data returned by the contract to the caller.
+    return_data: Vec<u8>,
 }
 
 impl Frame {
@@ -24,43 +41,90 @@ impl Frame {
         self.contract_id.clone()
     }
 
+    pub fn return_data(&self) -> &[u8] {
+        &self.return_data
+    }
+
     pub fn payment_id(&self) -> Vec<u8> {
         PaymentId::new(self.contract_id.clone(), self.nonce).as_bytes()
     }
 }
 
+/// Fuel consumed by a frame of the call stack, including the frames it called.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameFuel {
+    pub contract_id: Vec<u8>,
+    pub func_name: String,
+    /// Depth in the call stack, `0` for the top-level call.
+    pub depth: usize,
+    pub fuel_consumed: u64,
+}
+
 /// The entry point for the virtual machine.
 /// Contains:
 /// * Call stack
 /// * WASM environment
 /// * Interface to interact with node (or simulation)
+///
+/// The node is any [`Node`] implementation: the JNI bridge to the Waves Enterprise Node,
+/// the in-memory [`Ledger`](crate::node::ledger::Ledger) or a custom backend.
+///
+/// State changes are buffered in a [`Journal`] per frame. The journal of a successful
+/// call is merged into the journal of the caller and discarded otherwise, and the node
+/// receives the changes only after the top-level call succeeds. Issue and lease are
+/// the exception: their identifiers are assigned by the node, so they are applied immediately
+/// and compensated by a burn or a lease cancel if the call that made them fails.
 pub struct Vm {
     frames: Vec<Frame>,
     first_frame: Frame,
-    memory: (u32, u32),
+    config: VmConfig,
     fuel_limit: u64,
+    /// Fuel remaining for all frames of the call stack.
+    fuel: u64,
+    /// Fuel consumed by each executed frame in the order of calls.
+    fuel_report: Vec<FrameFuel>,
     modules: Vec<Module>,
-    pub jvm: Option<JavaVM>,
-    pub jvm_callback: Option<GlobalRef>,
+    cost_schedule: CostSchedule,
+    node: Box<dyn Node>,
+    /// Journals of the frames being executed, the last one belongs to the top frame.
+    journals: RefCell<Vec<Journal>>,
+    /// State changes passed to the node in the order they were made.
+    changes: RefCell<Vec<Operation>>,
+    /// Balances read from the node, which only change them on the operations
+    /// applied at once, on the compensations and on the commit.
+    node_balances: RefCell<HashMap<BalanceKey, i64>>,
+    /// Contracts are only allowed to read the state.
+    read_only: bool,
+    /// Time by which the top-level call must be completed.
+    deadline: Option<Instant>,
+    /// Failure of the deepest frame, kept while the error is passed to the callers.
+    error_report: Option<ErrorReport>,
+    /// Message passed to `require` by the contract being executed.
+    require_message: RefCell<Option<Vec<u8>>>,
     nonce: u64,
 }
 
 impl Vm {
     /// VM initialization.
     /// During initialization, the first contract is placed on the stack of the call.
-    pub fn new(
+    /// An inconsistent config is rejected with [`ExecutableError::InvalidConfig`].
+    pub fn new<N: Node + 'static>(
         contract_id: Vec<u8>,
         bytecode: Vec<u8>,
-        memory: (u32, u32),
+        config: VmConfig,
         fuel_limit: u64,
         modules: Vec<Module>,
-        jvm: Option<JavaVM>,
-        jvm_callback: Option<GlobalRef>,
+        cost_schedule: CostSchedule,
+        node: N,
     ) -> Result<Self> {
+        config.validate()?;
+
         let first_frame = Frame {
             contract_id,
             bytecode,
+            func_name: String::new(),
             nonce: 0,
+            return_data: vec![],
         };
 
         debug!(
@@ -71,17 +135,27 @@ impl Vm {
         Ok(Self {
             frames: Default::default(),
             first_frame,
-            memory,
+            config,
             fuel_limit,
+            fuel: fuel_limit,
+            fuel_report: Default::default(),
             modules,
-            jvm,
-            jvm_callback,
+            cost_schedule,
+            node: Box::new(node),
+            journals: Default::default(),
+            changes: Default::default(),
+            node_balances: Default::default(),
+            read_only: false,
+            deadline: None,
+            error_report: None,
+            require_message: Default::default(),
             nonce: 0,
         })
     }
 
     /// Calling another contract when a contract is executed.
-    /// Contract is placed on top of the call stack.
+    /// Contract is placed on top of the call stack together with the payments attached to the call.
+    /// Returns the result of the function and the data returned by the contract.
     pub fn call(
         &mut self,
         contract_id: Vec<u8>,
@@ -89,42 +163,58 @@ impl Vm {
         nonce: u64,
         func_name: &str,
         params: &[u8],
-    ) -> Result<Vec<Value>> {
+        payments: &[u8],
+    ) -> Result<(Vec<Value>, Vec<u8>)> {
+        let caller_id = self.top_frame().contract_id();
         let frame = Frame {
             contract_id,
             bytecode,
+            func_name: func_name.to_string(),
             nonce,
+            return_data: vec![],
         };
 
         debug!(
             "The contract with id: {} triggers the contract with id: {} to call the function: {}",
-            self.top_frame().contract_id().to_base58(),
+            caller_id.to_base58(),
             frame.contract_id.to_base58(),
             func_name
         );
 
+        let payment_id = frame.payment_id();
         self.push_frame(frame)?;
-        self.run(func_name, params)
-    }
+        self.journals.get_mut().push(Journal::default());
 
-    /// Run contract. The contract is taken from the top of the call stack.
-    pub fn run(&mut self, func_name: &str, params: &[u8]) -> Result<Vec<Value>> {
-        let frame = self.top_frame();
+        let result = if payments.is_empty() {
+            self.execute(func_name, params)
+        } else {
+            self.add_payments(&caller_id, &payment_id, payments)
+                .and_then(|_| self.execute(func_name, params))
+        };
+        let result = self.close_journal(result);
 
-        let func_name = LoadableFunction::from_str(func_name)?;
+        let return_data = match self.frames.pop() {
+            Some(frame) => frame.return_data,
+            None => vec![],
+        };
 
-        let mut exec = Executable::new(self.memory.0, self.memory.1, self.fuel_limit);
-        exec.load_bytecode(&frame.bytecode)?;
+        result.map(|result| (result, return_data))
+    }
 
-        debug!(
-            "Calling the function: {} contract: {}",
-            func_name.to_string(),
-            self.top_frame().contract_id().to_base58()
-        );
+    /// Run contract. The contract is taken from the top of the call stack.
+    /// The timeout of the config is counted from this call.
+    pub fn run(&mut self, func_name: &str, params: &[u8]) -> Result<Vec<Value>> {
+        self.deadline = self.config.timeout.map(|timeout| Instant::now() + timeout);
+        self.first_frame.func_name = func_name.to_string();
+        self.error_report = None;
+        self.require_message.get_mut().take();
 
-        let result = exec.execute(&func_name, params, self.modules.clone(), self);
+        self.journals.get_mut().push(Journal::default());
+        let result = self.execute(func_name, params);
+        let result = self.close_journal(result);
 
-        self.frames.pop();
+        // Changes are committed to the node after the frames are completed
+        self.record_error(&result);
 
         result
     }
@@ -134,6 +224,78 @@ impl Vm {
         self.frames.last().unwrap_or(&self.first_frame)
     }
 
+    /// Setting the data returned by the contract at the top of the call stack.
+    pub fn set_return_data(&mut self, data: Vec<u8>) {
+        match self.frames.last_mut() {
+            Some(frame) => frame.return_data = data,
+            None => self.first_frame.return_data = data,
+        }
+    }
+
+    /// Getting the data returned by the first contract of the call stack.
+    pub fn return_data(&self) -> &[u8] {
+        self.first_frame.return_data()
+    }
+
+    /// Getting the fuel remaining for the execution.
+    pub fn fuel(&self) -> u64 {
+        self.fuel
+    }
+
+    /// Getting the fuel consumed by all frames since the VM was initialized.
+    pub fn fuel_consumed(&self) -> u64 {
+        self.fuel_limit.saturating_sub(self.fuel)
+    }
+
+    /// Getting the failure of the last execution: the error or the error code returned
+    /// by the contract, together with the frames of the call stack where it occurred.
+    pub fn error_report(&self) -> Option<&ErrorReport> {
+        self.error_report.as_ref()
+    }
+
+    /// Getting the fuel consumed by each executed frame in the order of calls.
+    pub fn fuel_report(&self) -> &[FrameFuel] {
+        &self.fuel_report
+    }
+
+    /// Getting the state changes passed to the node, the fuel consumed
+    /// and the calls made since the VM was initialized.
+    pub fn execution_result(&self) -> Result<ExecutionResult> {
+        ExecutionResult::new(
+            &self.changes.borrow(),
+            self.fuel_consumed(),
+            &self.fuel_report,
+        )
+    }
+
+    /// Setting the fuel remaining for the execution.
+    /// The next frame is executed with exactly this amount of fuel.
+    pub fn set_fuel(&mut self, fuel: u64) {
+        self.fuel = fuel;
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Setting the read-only mode, in which any state change fails
+    /// with [`RuntimeError::ReadOnlyViolation`].
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Checking whether the deadline of the top-level call has passed.
+    /// The deadline is checked on entering a frame, on each host function call
+    /// and after a frame is completed.
+    pub fn is_timed_out(&self) -> bool {
+        matches!(self.deadline, Some(deadline) if Instant::now() >= deadline)
+    }
+
+    /// Getting the fuel costs of host functions.
+    pub fn cost_schedule(&self) -> &CostSchedule {
+        &self.cost_schedule
+    }
+
     pub fn get_nonce(&mut self) -> u64 {
         self.nonce += 1;
         self.nonce
@@ -153,8 +315,238 @@ impl Vm {
         }
     }
 
+    /// Recording the operation in the journal of the top frame.
+    /// Outside of the execution the operation is applied to the node immediately.
+    fn record(&self, operation: Operation) -> Result<()> {
+        self.check_writable()?;
+
+        if self.journals.borrow().is_empty() {
+            self.node_balances.borrow_mut().clear();
+            return operation.apply(self.node.as_ref());
+        }
+
+        // Funds are checked at once, as the node would reject the operation
+        let mut debits: HashMap<Vec<u8>, i64> = HashMap::new();
+        for (asset_id, amount) in operation.debits()? {
+            let debit = debits.entry(asset_id).or_default();
+            *debit = debit.saturating_add(amount);
+        }
+
+        let holder = Journal::contract(operation.contract_id());
+        for (asset_id, amount) in debits {
+            let balance = self.get_balance(&asset_id, &holder)?;
+            if balance < amount {
+                return Err(Error::Runtime(RuntimeError::NodeRejected(format!(
+                    "Insufficient funds: {} < {}",
+                    balance, amount
+                ))));
+            }
+        }
+
+        match self.journals.borrow_mut().last_mut() {
+            Some(journal) => journal.push(operation),
+            None => Ok(()),
+        }
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        match self.read_only {
+            true => Err(Error::Runtime(RuntimeError::ReadOnlyViolation)),
+            false => Ok(()),
+        }
+    }
+
+    /// Getting the latest buffered value of the key.
+    fn journal_storage(&self, address: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+        self.journals
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|journal| journal.storage(address, key).cloned())
+    }
+
+    /// Getting the buffered payments attached to the call.
+    fn journal_payments(&self, payment_id: &[u8]) -> Option<Vec<(Vec<u8>, i64)>> {
+        self.journals
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|journal| journal.payments(payment_id).cloned())
+    }
+
+    fn journal_payment(&self, payment_id: &[u8], number: i64) -> Option<Result<(Vec<u8>, i64)>> {
+        let payments = self.journal_payments(payment_id)?;
+
+        Some(match payments.get(number as usize) {
+            Some(payment) => Ok(payment.clone()),
+            None => Err(Error::Runtime(RuntimeError::NodeRejected(
+                "Payment not found".to_string(),
+            ))),
+        })
+    }
+
+    /// Executing the function of the contract at the top of the call stack.
+    /// A failure is recorded together with the call stack.
+    fn execute(&mut self, func_name: &str, params: &[u8]) -> Result<Vec<Value>> {
+        let result = self.execute_frame(func_name, params);
+        self.record_error(&result);
+        result
+    }
+
+    fn execute_frame(&mut self, func_name: &str, params: &[u8]) -> Result<Vec<Value>> {
+        if self.is_timed_out() {
+            return Err(Error::Executable(ExecutableError::Timeout));
+        }
+
+        let frame = self.top_frame();
+
+        let func_name = LoadableFunction::from_str(func_name)?;
+
+        if frame.bytecode.len() > self.config.max_bytecode_size {
+            return Err(Error::Executable(ExecutableError::BytecodeSizeLimit(
+                format!(
+                    "Bytecode size {} exceeds the limit {}",
+                    frame.bytecode.len(),
+                    self.config.max_bytecode_size
+                ),
+            )));
+        }
+
+        if params.len() > self.config.max_params_size {
+            return Err(Error::Executable(ExecutableError::ParamsSizeLimit(
+                format!(
+                    "Arguments size {} exceeds the limit {}",
+                    params.len(),
+                    self.config.max_params_size
+                ),
+            )));
+        }
+
+        let mut exec = Executable::new(&self.config, self.fuel);
+        exec.load_bytecode(&frame.bytecode)?;
+
+        debug!(
+            "Calling the function: {} contract: {}",
+            func_name,
+            self.top_frame().contract_id().to_base58()
+        );
+
+        let index = self.fuel_report.len();
+        self.fuel_report.push(FrameFuel {
+            contract_id: self.top_frame().contract_id(),
+            func_name: func_name.to_string(),
+            depth: self.frames.len(),
+            fuel_consumed: 0,
+        });
+        let fuel = self.fuel;
+
+        let result = exec.execute(&func_name, params, self.modules.clone(), self);
+
+        self.fuel_report[index].fuel_consumed = fuel.saturating_sub(self.fuel);
+
+        // A host function may have been blocked by the node after the last check
+        if self.is_timed_out() {
+            return Err(Error::Executable(ExecutableError::Timeout));
+        }
+
+        result
+    }
+
+    /// Closing the journal of the top frame.
+    /// Changes of a successful call are passed to the caller or to the node
+    /// if there is no caller, changes of a failed call are discarded
+    /// and the operations already applied by the node are compensated.
+    fn close_journal(&mut self, result: Result<Vec<Value>>) -> Result<Vec<Value>> {
+        let journal = self.journals.get_mut().pop().unwrap_or_default();
+
+        if !Self::is_success(&result) {
+            let rollback = journal.rollback(self.node.as_ref(), self.changes.get_mut());
+            self.node_balances.get_mut().clear();
+            rollback?;
+            return result;
+        }
+
+        match self.journals.get_mut().last_mut() {
+            Some(parent) => parent.merge(journal),
+            None => {
+                // Nothing is applied if the funds do not cover the whole journal
+                journal.check_funds(|asset_id, holder| self.node_balance(asset_id, holder))?;
+
+                let commit = journal.commit(self.node.as_ref(), self.changes.get_mut());
+                self.node_balances.get_mut().clear();
+                commit?;
+            }
+        }
+
+        result
+    }
+
+    /// Getting the balance from the node, each balance is requested once.
+    fn node_balance(&self, asset_id: &[u8], holder: &[u8]) -> Result<i64> {
+        let key = (asset_id.to_vec(), holder.to_vec());
+
+        if let Some(balance) = self.node_balances.borrow().get(&key) {
+            return Ok(*balance);
+        }
+
+        let balance = self.node.get_balance(asset_id, holder)?;
+        self.node_balances.borrow_mut().insert(key, balance);
+
+        Ok(balance)
+    }
+
+    /// Listing the operation applied by the node at once.
+    fn applied(&self, operation: &Operation) {
+        self.node_balances.borrow_mut().clear();
+        self.changes.borrow_mut().push(operation.clone());
+    }
+
+    /// Recording the failure of the top frame. A failure passed unchanged from a callee
+    /// keeps the report of the callee, so the report points to where it occurred.
+    fn record_error(&mut self, result: &Result<Vec<Value>>) {
+        let report = match result {
+            Err(error) => match &self.error_report {
+                Some(report) if report.describes(error) => return,
+                _ => ErrorReport::new(error, self.error_frames()),
+            },
+            Ok(values) => match values.as_slice() {
+                [Value::I32(code)] if *code != 0 => match &self.error_report {
+                    Some(report) if report.code == *code => return,
+                    _ => {
+                        let message = self
+                            .require_message
+                            .get_mut()
+                            .take()
+                            .map(|message| String::from_utf8_lossy(&message).into_owned())
+                            .unwrap_or_default();
+                        ErrorReport::contract(*code, message, self.error_frames())
+                    }
+                },
+                _ => return,
+            },
+        };
+
+        self.error_report = Some(report);
+    }
+
+    /// Getting the frames of the call stack from the top-level call.
+    fn error_frames(&self) -> Vec<ErrorFrame> {
+        std::iter::once(&self.first_frame)
+            .chain(self.frames.iter())
+            .map(|frame| ErrorFrame {
+                contract_id: frame.contract_id(),
+                func_name: frame.func_name.clone(),
+            })
+            .collect()
+    }
+
+    /// The call succeeds if it is completed and returns no error code.
+    fn is_success(result: &Result<Vec<Value>>) -> bool {
+        matches!(result, Ok(values) if matches!(values.as_slice(), [Value::I32(0)]))
+    }
+
     fn push_frame(&mut self, frame: Frame) -> Result<()> {
-        if self.frames.len() == MAX_FRAMES {
+        if self.frames.len() >= self.config.max_call_depth {
             return Err(Error::Executable(ExecutableError::StackOverflow));
         }
 
@@ -163,3 +555,258 @@ impl Vm {
         Ok(())
     }
 }
+
+// All interaction with the node goes through the backend the VM was created with,
+// state changes are buffered in the journal until the top-level call succeeds
+impl Node for Vm {
+    fn get_chain_id(&self) -> Result<i8> {
+        self.node.get_chain_id()
+    }
+
+    fn require(&self, message: &[u8]) -> Result<()> {
+        *self.require_message.borrow_mut() = Some(message.to_vec());
+        self.node.require(message)
+    }
+
+    fn get_bytecode(&self, contract_id: &[u8]) -> Result<Vec<u8>> {
+        self.node.get_bytecode(contract_id)
+    }
+
+    fn add_payments(&self, contract_id: &[u8], payment_id: &[u8], payments: &[u8]) -> Result<()> {
+        self.record(Operation::AddPayments {
+            contract_id: contract_id.to_vec(),
+            payment_id: payment_id.to_vec(),
+            payments: payments.to_vec(),
+        })
+    }
+
+    // Asset
+    fn get_balance(&self, asset_id: &[u8], address: &[u8]) -> Result<i64> {
+        let delta: i64 = self
+            .journals
+            .borrow()
+            .iter()
+            .map(|journal| journal.balance(asset_id, address))
+            .sum();
+
+        Ok(self.node_balance(asset_id, address)?.saturating_add(delta))
+    }
+
+    fn transfer(
+        &self,
+        contract_id: &[u8],
+        asset_id: &[u8],
+        recipient: &[u8],
+        amount: i64,
+    ) -> Result<()> {
+        self.record(Operation::Transfer {
+            contract_id: contract_id.to_vec(),
+            asset_id: asset_id.to_vec(),
+            recipient: recipient.to_vec(),
+            amount,
+        })
+    }
+
+    fn issue(
+        &self,
+        contract_id: &[u8],
+        name: &[u8],
+        description: &[u8],
+        quantity: i64,
+        decimals: i64,
+        is_reissuable: bool,
+    ) -> Result<Vec<u8>> {
+        self.check_writable()?;
+
+        let asset_id = self.node.issue(
+            contract_id,
+            name,
+            description,
+            quantity,
+            decimals,
+            is_reissuable,
+        )?;
+
+        let operation = Operation::Issue {
+            contract_id: contract_id.to_vec(),
+            asset_id: asset_id.clone(),
+            name: name.to_vec(),
+            description: description.to_vec(),
+            quantity,
+            decimals,
+            is_reissuable,
+        };
+        self.applied(&operation);
+        self.record(operation)?;
+
+        Ok(asset_id)
+    }
+
+    fn burn(&self, contract_id: &[u8], asset_id: &[u8], amount: i64) -> Result<()> {
+        self.record(Operation::Burn {
+            contract_id: contract_id.to_vec(),
+            asset_id: asset_id.to_vec(),
+            amount,
+        })
+    }
+
+    fn reissue(
+        &self,
+        contract_id: &[u8],
+        asset_id: &[u8],
+        amount: i64,
+        is_reissuable: bool,
+    ) -> Result<()> {
+        self.record(Operation::Reissue {
+            contract_id: contract_id.to_vec(),
+            asset_id: asset_id.to_vec(),
+            amount,
+            is_reissuable,
+        })
+    }
+
+    // Block
+    fn block(&self, field: &[u8]) -> Result<Vec<u8>> {
+        self.node.block(field)
+    }
+
+    // Crypto
+    fn fast_hash(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        self.node.fast_hash(bytes)
+    }
+
+    fn secure_hash(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        self.node.secure_hash(bytes)
+    }
+
+    fn sig_verify(&self, message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool> {
+        self.node.sig_verify(message, signature, public_key)
+    }
+
+    // Lease
+    fn lease(&self, contract_id: &[u8], recipient: &[u8], amount: i64) -> Result<Vec<u8>> {
+        self.check_writable()?;
+
+        let lease_id = self.node.lease(contract_id, recipient, amount)?;
+
+        let operation = Operation::Lease {
+            contract_id: contract_id.to_vec(),
+            lease_id: lease_id.clone(),
+            recipient: recipient.to_vec(),
+            amount,
+        };
+        self.applied(&operation);
+        self.record(operation)?;
+
+        Ok(lease_id)
+    }
+
+    fn cancel_lease(&self, contract_id: &[u8], lease_id: &[u8]) -> Result<()> {
+        self.record(Operation::CancelLease {
+            contract_id: contract_id.to_vec(),
+            lease_id: lease_id.to_vec(),
+        })
+    }
+
+    // Storage
+    fn contains_key(&self, address: &[u8], key: &[u8]) -> Result<bool> {
+        match self.journal_storage(address, key) {
+            Some(value) => Ok(!value.is_empty()),
+            None => self.node.contains_key(address, key),
+        }
+    }
+
+    fn get_storage(&self, address: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+        match self.journal_storage(address, key) {
+            Some(value) => Ok(value),
+            None => self.node.get_storage(address, key),
+        }
+    }
+
+    fn set_storage(&self, contract_id: &[u8], value: &[u8]) -> Result<()> {
+        self.record(Operation::SetStorage {
+            contract_id: contract_id.to_vec(),
+            value: value.to_vec(),
+        })
+    }
+
+    fn set_storage_batch(&self, contract_id: &[u8], values: &[u8]) -> Result<()> {
+        // The values are buffered separately and batched again on commit
+        for value in DataEntry::split_batch(values)? {
+            self.record(Operation::SetStorage {
+                contract_id: contract_id.to_vec(),
+                value,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn delete_storage(&self, contract_id: &[u8], key: &[u8]) -> Result<()> {
+        self.record(Operation::DeleteStorage {
+            contract_id: contract_id.to_vec(),
+            key: key.to_vec(),
+        })
+    }
+
+    fn storage_keys(
+        &self,
+        address: &[u8],
+        prefix: &[u8],
+        cursor: &[u8],
+        limit: u32,
+    ) -> Result<Vec<Vec<u8>>> {
+        // Buffered keys, the latest write wins
+        let mut buffered: BTreeMap<Vec<u8>, bool> = BTreeMap::new();
+        for journal in self.journals.borrow().iter() {
+            for (key, value) in journal.storage_entries(address) {
+                if key.starts_with(prefix) && key.as_slice() > cursor {
+                    buffered.insert(key.clone(), !value.is_empty());
+                }
+            }
+        }
+
+        // Each buffered key may hide a key of the node
+        let extended = limit.saturating_add(buffered.len() as u32);
+        let mut keys: BTreeSet<Vec<u8>> = self
+            .node
+            .storage_keys(address, prefix, cursor, extended)?
+            .into_iter()
+            .filter(|key| !buffered.contains_key(key))
+            .collect();
+
+        keys.extend(
+            buffered
+                .into_iter()
+                .filter_map(|(key, exists)| exists.then_some(key)),
+        );
+
+        Ok(keys.into_iter().take(limit as usize).collect())
+    }
+
+    // Tx
+    fn get_tx_payments(&self, payment_id: &[u8]) -> Result<i64> {
+        match self.journal_payments(payment_id) {
+            Some(payments) => Ok(payments.len() as i64),
+            None => self.node.get_tx_payments(payment_id),
+        }
+    }
+
+    fn get_tx_payment_asset_id(&self, payment_id: &[u8], number: i64) -> Result<Vec<u8>> {
+        match self.journal_payment(payment_id, number) {
+            Some(payment) => payment.map(|(asset_id, _)| asset_id),
+            None => self.node.get_tx_payment_asset_id(payment_id, number),
+        }
+    }
+
+    fn get_tx_payment_amount(&self, payment_id: &[u8], number: i64) -> Result<i64> {
+        match self.journal_payment(payment_id, number) {
+            Some(payment) => payment.map(|(_, amount)| amount),
+            None => self.node.get_tx_payment_amount(payment_id, number),
+        }
+    }
+
+    fn tx(&self, field: &[u8]) -> Result<Vec<u8>> {
+        self.node.tx(field)
+    }
+}
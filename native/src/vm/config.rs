@@ -0,0 +1,327 @@
+use crate::{
+    error::{Error, ExecutableError, Result},
+    exec::lint::LintLimits,
+    runtime::utils,
+    vm::cost_schedule::Cost,
+    MEMORY,
+};
+use std::time::Duration;
+use wasmi::StackLimits;
+
+/// Limits of the Wasm stack of a contract, heights are given in values.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct StackConfig {
+    pub initial_value_stack_height: usize,
+    pub maximum_value_stack_height: usize,
+    /// Maximum number of nested calls of functions within a contract.
+    pub maximum_recursion_depth: usize,
+}
+
+impl Default for StackConfig {
+    /// Same as `StackLimits::default()`.
+    fn default() -> Self {
+        Self {
+            initial_value_stack_height: 128,
+            maximum_value_stack_height: 128 * 1024,
+            maximum_recursion_depth: 1024,
+        }
+    }
+}
+
+impl StackConfig {
+    pub fn stack_limits(&self) -> Result<StackLimits> {
+        StackLimits::new(
+            self.initial_value_stack_height,
+            self.maximum_value_stack_height,
+            self.maximum_recursion_depth,
+        )
+        .map_err(|error| Error::Executable(ExecutableError::InvalidConfig(error.to_string())))
+    }
+}
+
+/// Execution limits of the VM, passed with each call from the node.
+///
+/// Serialized as the `u32` initial and maximum memory pages, call depth, initial and maximum
+/// value stack heights, recursion depth, bytecode size, arguments size and the lint limits
+/// (memory pages, table size, data segment size, locals and functions), followed by
+/// the `u64` timeout in milliseconds, `0` for no timeout, and the `u16` count of the overridden
+/// costs of host functions. Each cost is the module and function name, prefixed with the `u16`
+/// length, followed by the `u64` base, per byte and per entry fuel. Empty bytes give
+/// the default config.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VmConfig {
+    /// Initial and maximum number of pages of memory of a contract.
+    pub memory: (u32, u32),
+    /// Maximum number of contracts in the call stack.
+    pub max_call_depth: usize,
+    pub stack: StackConfig,
+    /// Maximum size of the bytecode of a contract in bytes.
+    pub max_bytecode_size: usize,
+    /// Maximum size of the serialized function arguments in bytes.
+    pub max_params_size: usize,
+    /// Limits the bytecode is checked against on validation.
+    pub lint: LintLimits,
+    /// Wall-clock time limit of the top-level call, including the calls to the node.
+    pub timeout: Option<Duration>,
+    /// Costs of host functions as `(module, name, cost)`, overriding the default cost schedule.
+    pub costs: Vec<(String, String, Cost)>,
+}
+
+impl Default for VmConfig {
+    fn default() -> Self {
+        Self {
+            memory: MEMORY,
+            max_call_depth: 64,
+            stack: StackConfig::default(),
+            max_bytecode_size: 4 * 1024 * 1024,
+            max_params_size: 1024 * 1024,
+            lint: LintLimits::default(),
+            timeout: None,
+            costs: vec![],
+        }
+    }
+}
+
+impl VmConfig {
+    /// Checking that the limits are consistent.
+    pub fn validate(&self) -> Result<()> {
+        let error = |message: &str| {
+            Err(Error::Executable(ExecutableError::InvalidConfig(
+                message.to_string(),
+            )))
+        };
+
+        if self.memory.0 > self.memory.1 {
+            return error("Initial memory exceeds the maximum");
+        }
+
+        // Memory of a 32-bit address space
+        if self.memory.1 > 65536 {
+            return error("Maximum memory exceeds 65536 pages");
+        }
+
+        if self.max_call_depth == 0 {
+            return error("Maximum call depth is zero");
+        }
+
+        self.stack.stack_limits()?;
+
+        let signatures = crate::signatures();
+        for (module, name, _) in self.costs.iter() {
+            if !signatures.iter().any(|(m, n, _)| m == module && n == name) {
+                return Err(Error::Executable(ExecutableError::InvalidConfig(format!(
+                    "Unknown host function {}.{}",
+                    module, name
+                ))));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reading the config passed by the node, an inconsistent config is rejected.
+    pub fn deserialize(input: &[u8]) -> Result<Self> {
+        if input.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut offset = 0;
+        // The values are written as `Int` by the JVM, so a negative one would disable the limit
+        let mut get_u32 = || match utils::get_u32(input, &mut offset).map_err(unexpected_end)? {
+            value if value > i32::MAX as u32 => Err(Error::Executable(
+                ExecutableError::InvalidConfig(format!("Negative value {}", value as i32)),
+            )),
+            value => Ok(value),
+        };
+
+        let memory = (get_u32()?, get_u32()?);
+        let max_call_depth = get_u32()? as usize;
+        let stack = StackConfig {
+            initial_value_stack_height: get_u32()? as usize,
+            maximum_value_stack_height: get_u32()? as usize,
+            maximum_recursion_depth: get_u32()? as usize,
+        };
+        let max_bytecode_size = get_u32()? as usize;
+        let max_params_size = get_u32()? as usize;
+        let lint = LintLimits {
+            max_memory_pages: get_u32()?,
+            max_table_size: get_u32()?,
+            max_data_segment_size: get_u32()?,
+            max_locals: get_u32()?,
+            max_functions: get_u32()?,
+        };
+
+        let timeout = match utils::get_u64(input, &mut offset).map_err(unexpected_end)? {
+            0 => None,
+            millis => Some(Duration::from_millis(millis)),
+        };
+
+        let count = utils::get_u16(input, &mut offset).map_err(unexpected_end)?;
+        let mut costs = vec![];
+        for _ in 0..count {
+            let module = get_string(input, &mut offset)?;
+            let name = get_string(input, &mut offset)?;
+            let cost = Cost {
+                base: get_i64(input, &mut offset)?,
+                per_byte: get_i64(input, &mut offset)?,
+                per_entry: get_i64(input, &mut offset)?,
+            };
+            costs.push((module, name, cost));
+        }
+
+        if offset != input.len() {
+            return Err(Error::Executable(ExecutableError::InvalidConfig(
+                "Unexpected bytes at the end of the config".to_string(),
+            )));
+        }
+
+        let config = Self {
+            memory,
+            max_call_depth,
+            stack,
+            max_bytecode_size,
+            max_params_size,
+            lint,
+            timeout,
+            costs,
+        };
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut result = vec![];
+
+        for value in [
+            self.memory.0,
+            self.memory.1,
+            self.max_call_depth as u32,
+            self.stack.initial_value_stack_height as u32,
+            self.stack.maximum_value_stack_height as u32,
+            self.stack.maximum_recursion_depth as u32,
+            self.max_bytecode_size as u32,
+            self.max_params_size as u32,
+            self.lint.max_memory_pages,
+            self.lint.max_table_size,
+            self.lint.max_data_segment_size,
+            self.lint.max_locals,
+            self.lint.max_functions,
+        ] {
+            result.extend_from_slice(&value.to_be_bytes());
+        }
+
+        let timeout = self.timeout.map_or(0, |timeout| timeout.as_millis() as u64);
+        result.extend_from_slice(&timeout.to_be_bytes());
+
+        result.extend_from_slice(&(self.costs.len() as u16).to_be_bytes());
+        for (module, name, cost) in self.costs.iter() {
+            for value in [module, name] {
+                result.extend_from_slice(&(value.len() as u16).to_be_bytes());
+                result.extend_from_slice(value.as_bytes());
+            }
+
+            for value in [cost.base, cost.per_byte, cost.per_entry] {
+                result.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+
+        result
+    }
+}
+
+fn unexpected_end(_: Error) -> Error {
+    Error::Executable(ExecutableError::InvalidConfig(
+        "Unexpected end of the config".to_string(),
+    ))
+}
+
+/// Reading a string prefixed with the `u16` length.
+fn get_string(input: &[u8], offset: &mut usize) -> Result<String> {
+    let length = utils::get_u16(input, offset).map_err(unexpected_end)?;
+    let bytes = utils::get_bytes(input, offset, length as usize).map_err(unexpected_end)?;
+
+    String::from_utf8(bytes).map_err(|_| {
+        Error::Executable(ExecutableError::InvalidConfig(
+            "Invalid host function name".to_string(),
+        ))
+    })
+}
+
+/// Reading a `u64` written as `Long` by the JVM, a negative value is rejected.
+fn get_i64(input: &[u8], offset: &mut usize) -> Result<u64> {
+    match utils::get_u64(input, offset).map_err(unexpected_end)? {
+        value if value > i64::MAX as u64 => Err(Error::Executable(ExecutableError::InvalidConfig(
+            format!("Negative value {}", value as i64),
+        ))),
+        value => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let config = VmConfig::default();
+        assert_eq!(config.validate(), Ok(()));
+        assert!(config.stack.stack_limits().is_ok());
+
+        let invalid = VmConfig {
+            memory: (4, 2),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+        assert!(VmConfig::deserialize(&invalid.as_bytes()).is_err());
+
+        let invalid = VmConfig {
+            stack: StackConfig {
+                initial_value_stack_height: 2,
+                maximum_value_stack_height: 1,
+                maximum_recursion_depth: 1,
+            },
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+
+        assert_eq!(VmConfig::deserialize(&[]), Ok(config));
+
+        let config = VmConfig {
+            memory: (1, 4),
+            lint: LintLimits {
+                max_locals: 4096,
+                ..Default::default()
+            },
+            timeout: Some(Duration::from_millis(250)),
+            costs: vec![(
+                "env0".to_string(),
+                "sha256".to_string(),
+                Cost::new(10, 2).with_per_entry(1),
+            )],
+            ..Default::default()
+        };
+        assert_eq!(
+            VmConfig::deserialize(&config.as_bytes()),
+            Ok(config.clone())
+        );
+        assert!(VmConfig::deserialize(&config.as_bytes()[1..]).is_err());
+
+        let mut bytes = config.as_bytes();
+        // Maximum number of locals set to -1
+        bytes[44..48].copy_from_slice(&(-1i32).to_be_bytes());
+        assert!(VmConfig::deserialize(&bytes).is_err());
+
+        let mut bytes = config.as_bytes();
+        // Per entry cost of `sha256` set to -1
+        let length = bytes.len();
+        bytes[length - 8..].copy_from_slice(&(-1i64).to_be_bytes());
+        assert!(VmConfig::deserialize(&bytes).is_err());
+
+        let invalid = VmConfig {
+            costs: vec![("env0".to_string(), "unknown".to_string(), Cost::default())],
+            ..Default::default()
+        };
+        assert!(VmConfig::deserialize(&invalid.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+/// Fuel cost of a host function call.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Cost {
+    /// Fuel charged for each call.
+    pub base: u64,
+    /// Fuel charged for each byte of the data passed to the call and written to memory by it.
+    pub per_byte: u64,
+    /// Fuel charged for each entry requested from the node by the call.
+    pub per_entry: u64,
+}
+
+impl Cost {
+    pub const fn new(base: u64, per_byte: u64) -> Self {
+        Self {
+            base,
+            per_byte,
+            per_entry: 0,
+        }
+    }
+
+    /// Setting the fuel charged for each entry requested by the call.
+    pub const fn with_per_entry(mut self, per_entry: u64) -> Self {
+        self.per_entry = per_entry;
+        self
+    }
+
+    /// Getting the fuel cost of a call with the given size of the input data.
+    pub fn fuel(&self, bytes: u64) -> u64 {
+        self.base
+            .saturating_add(self.per_byte.saturating_mul(bytes))
+    }
+}
+
+/// Table of fuel costs of host functions, keyed by module and function name.
+/// Functions that are not in the table are charged the default cost.
+#[derive(Debug, Clone, Default)]
+pub struct CostSchedule {
+    default: Cost,
+    costs: HashMap<String, HashMap<String, Cost>>,
+}
+
+impl CostSchedule {
+    pub fn new(default: Cost) -> Self {
+        Self {
+            default,
+            costs: Default::default(),
+        }
+    }
+
+    /// Setting the cost of the host function.
+    pub fn set(&mut self, module: &str, name: &str, cost: Cost) -> &mut Self {
+        self.costs
+            .entry(module.to_string())
+            .or_default()
+            .insert(name.to_string(), cost);
+        self
+    }
+
+    /// Getting the cost of the host function.
+    pub fn get(&self, module: &str, name: &str) -> Cost {
+        self.costs
+            .get(module)
+            .and_then(|costs| costs.get(name))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// Getting the fuel cost of the host function call with the given size of the input data.
+    pub fn fuel(&self, module: &str, name: &str, bytes: u64) -> u64 {
+        self.get(module, name).fuel(bytes)
+    }
+
+    /// Getting the fuel cost of the entries requested by the host function.
+    pub fn entries_fuel(&self, module: &str, name: &str, entries: u64) -> u64 {
+        self.get(module, name).per_entry.saturating_mul(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_schedule() {
+        let mut schedule = CostSchedule::new(Cost::new(1, 0));
+        schedule.set("env0", "sha256", Cost::new(10, 2));
+
+        assert_eq!(schedule.fuel("env0", "sha256", 32), 74);
+        assert_eq!(schedule.fuel("env1", "sha256", 32), 1);
+        assert_eq!(schedule.fuel("env0", "join", 32), 1);
+        assert_eq!(Cost::new(u64::MAX, 1).fuel(1), u64::MAX);
+
+        schedule.set("env3", "storage_keys", Cost::new(10, 1).with_per_entry(5));
+        assert_eq!(schedule.entries_fuel("env3", "storage_keys", 3), 15);
+        assert_eq!(schedule.entries_fuel("env0", "sha256", 3), 0);
+    }
+}
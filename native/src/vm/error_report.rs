@@ -0,0 +1,126 @@
+use crate::error::Error;
+
+/// Category of a failure, given by the error enumeration or by the contract itself.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ErrorCategory {
+    Executable,
+    Jvm,
+    Runtime,
+    /// Error code returned by the contract.
+    Contract,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Executable => "Executable",
+            ErrorCategory::Jvm => "Jvm",
+            ErrorCategory::Runtime => "Runtime",
+            ErrorCategory::Contract => "Contract",
+        }
+    }
+}
+
+/// Frame of the call stack in which a failure occurred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorFrame {
+    pub contract_id: Vec<u8>,
+    pub func_name: String,
+}
+
+/// Description of the failure of an execution passed to the node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorReport {
+    pub code: i32,
+    pub category: ErrorCategory,
+    /// Message of the error, or the message passed to `require` for a contract error.
+    pub message: String,
+    /// Frames from the top-level call to the frame where the failure occurred.
+    pub frames: Vec<ErrorFrame>,
+}
+
+impl ErrorReport {
+    pub fn new(error: &Error, frames: Vec<ErrorFrame>) -> Self {
+        let category = match error {
+            Error::Executable(_) => ErrorCategory::Executable,
+            Error::Jvm(_) => ErrorCategory::Jvm,
+            Error::Runtime(_) => ErrorCategory::Runtime,
+        };
+
+        Self {
+            code: error.as_i32(),
+            category,
+            message: error.to_string(),
+            frames,
+        }
+    }
+
+    /// Report of an error code returned by the contract.
+    pub fn contract(code: i32, message: String, frames: Vec<ErrorFrame>) -> Self {
+        Self {
+            code,
+            category: ErrorCategory::Contract,
+            message,
+            frames,
+        }
+    }
+
+    /// Whether the report describes the error, which is passed to the caller unchanged.
+    pub fn describes(&self, error: &Error) -> bool {
+        self.code == error.as_i32() && self.message == error.to_string()
+    }
+
+    /// Getting the contract in which the failure occurred.
+    pub fn contract_id(&self) -> &[u8] {
+        match self.frames.last() {
+            Some(frame) => &frame.contract_id,
+            None => &[],
+        }
+    }
+
+    /// Serializing the frames: the `u16` count of frames, each frame is given by
+    /// the contract id and the function name prefixed with the `u16` length.
+    pub fn frames_as_bytes(&self) -> Vec<u8> {
+        let mut result = (self.frames.len() as u16).to_be_bytes().to_vec();
+
+        for frame in self.frames.iter() {
+            result.extend_from_slice(&(frame.contract_id.len() as u16).to_be_bytes());
+            result.extend_from_slice(&frame.contract_id);
+            result.extend_from_slice(&(frame.func_name.len() as u16).to_be_bytes());
+            result.extend_from_slice(frame.func_name.as_bytes());
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RuntimeError;
+
+    #[test]
+    fn test_error_report() {
+        let error = Error::Runtime(RuntimeError::MemoryOutOfBounds);
+        let report = ErrorReport::new(
+            &error,
+            vec![ErrorFrame {
+                contract_id: vec![1, 2],
+                func_name: "run".to_string(),
+            }],
+        );
+
+        assert_eq!(report.category, ErrorCategory::Runtime);
+        assert!(report.describes(&error));
+        assert_eq!(report.contract_id(), &[1, 2]);
+        assert_eq!(
+            report.frames_as_bytes(),
+            vec![0, 1, 0, 2, 1, 2, 0, 3, b'r', b'u', b'n']
+        );
+
+        let report = ErrorReport::contract(1, String::new(), vec![]);
+        assert!(!report.describes(&error));
+        assert_eq!(report.contract_id(), &[] as &[u8]);
+        assert_eq!(report.frames_as_bytes(), vec![0, 0]);
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{
+    error::Result,
+    runtime::{data_entry::DataEntry, payments::Payments},
+    vm::{journal::Operation, FrameFuel},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageWrite {
+    pub contract_id: Vec<u8>,
+    pub key: Vec<u8>,
+    pub value: DataEntry,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    pub contract_id: Vec<u8>,
+    pub asset_id: Vec<u8>,
+    /// `AssetHolder` of the recipient.
+    pub recipient: Vec<u8>,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub contract_id: Vec<u8>,
+    pub asset_id: Vec<u8>,
+    pub name: Vec<u8>,
+    pub description: Vec<u8>,
+    pub quantity: i64,
+    pub decimals: i64,
+    pub is_reissuable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Burn {
+    pub contract_id: Vec<u8>,
+    pub asset_id: Vec<u8>,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reissue {
+    pub contract_id: Vec<u8>,
+    pub asset_id: Vec<u8>,
+    pub amount: i64,
+    pub is_reissuable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lease {
+    pub contract_id: Vec<u8>,
+    pub lease_id: Vec<u8>,
+    /// `AssetHolder` of the recipient.
+    pub recipient: Vec<u8>,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CancelLease {
+    pub contract_id: Vec<u8>,
+    pub lease_id: Vec<u8>,
+}
+
+/// Payments attached by the contract to the call of another contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallPayments {
+    pub contract_id: Vec<u8>,
+    pub payment_id: Vec<u8>,
+    pub payments: Payments,
+}
+
+/// State changes passed to the node by a successful execution,
+/// together with the fuel consumed and the tree of calls.
+///
+/// The changes of each kind are listed in the order they were made. Issues and leases
+/// of failed calls are listed as well, since the node applies them at once,
+/// together with the burns and lease cancels that compensate them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecutionResult {
+    pub storage: Vec<StorageWrite>,
+    pub transfers: Vec<Transfer>,
+    pub issues: Vec<Issue>,
+    pub burns: Vec<Burn>,
+    pub reissues: Vec<Reissue>,
+    pub leases: Vec<Lease>,
+    pub cancel_leases: Vec<CancelLease>,
+    pub payments: Vec<CallPayments>,
+    pub fuel_consumed: u64,
+    /// Executed frames in the order of calls, the tree is given by the depth of the frame.
+    pub calls: Vec<FrameFuel>,
+}
+
+impl ExecutionResult {
+    pub fn new(operations: &[Operation], fuel_consumed: u64, calls: &[FrameFuel]) -> Result<Self> {
+        let mut result = Self {
+            fuel_consumed,
+            calls: calls.to_vec(),
+            ..Default::default()
+        };
+
+        for operation in operations.iter().cloned() {
+            match operation {
+                Operation::SetStorage { contract_id, value } => {
+                    let (key, value) = DataEntry::deserialize_with_key(&value)?;
+                    result.storage.push(StorageWrite {
+                        contract_id,
+                        key,
+                        value,
+                    });
+                }
+                Operation::DeleteStorage { contract_id, key } => {
+                    result.storage.push(StorageWrite {
+                        contract_id,
+                        key,
+                        value: DataEntry::Deleted,
+                    })
+                }
+                Operation::Transfer {
+                    contract_id,
+                    asset_id,
+                    recipient,
+                    amount,
+                } => result.transfers.push(Transfer {
+                    contract_id,
+                    asset_id,
+                    recipient,
+                    amount,
+                }),
+                Operation::Issue {
+                    contract_id,
+                    asset_id,
+                    name,
+                    description,
+                    quantity,
+                    decimals,
+                    is_reissuable,
+                } => result.issues.push(Issue {
+                    contract_id,
+                    asset_id,
+                    name,
+                    description,
+                    quantity,
+                    decimals,
+                    is_reissuable,
+                }),
+                Operation::Burn {
+                    contract_id,
+                    asset_id,
+                    amount,
+                } => result.burns.push(Burn {
+                    contract_id,
+                    asset_id,
+                    amount,
+                }),
+                Operation::Reissue {
+                    contract_id,
+                    asset_id,
+                    amount,
+                    is_reissuable,
+                } => result.reissues.push(Reissue {
+                    contract_id,
+                    asset_id,
+                    amount,
+                    is_reissuable,
+                }),
+                Operation::Lease {
+                    contract_id,
+                    lease_id,
+                    recipient,
+                    amount,
+                } => result.leases.push(Lease {
+                    contract_id,
+                    lease_id,
+                    recipient,
+                    amount,
+                }),
+                Operation::CancelLease {
+                    contract_id,
+                    lease_id,
+                } => result.cancel_leases.push(CancelLease {
+                    contract_id,
+                    lease_id,
+                }),
+                Operation::AddPayments {
+                    contract_id,
+                    payment_id,
+                    payments,
+                } => result.payments.push(CallPayments {
+                    contract_id,
+                    payment_id,
+                    payments: Payments::deserialize(&payments)?,
+                }),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Getting byte representation of the execution result.
+    ///
+    /// Each list is prefixed with the number of items as `u32`, byte arrays are prefixed
+    /// with the length as `u32`, numbers are big-endian. The lists follow in the order:
+    /// * storage: `contract_id`, `DataEntry` serialized with the key,
+    ///   a deleted key has the type `255` and no value
+    /// * transfers: `contract_id`, `asset_id`, `recipient`, `amount: i64`
+    /// * issues: `contract_id`, `asset_id`, `name`, `description`, `quantity: i64`,
+    ///   `decimals: i64`, `is_reissuable: u8`
+    /// * burns: `contract_id`, `asset_id`, `amount: i64`
+    /// * reissues: `contract_id`, `asset_id`, `amount: i64`, `is_reissuable: u8`
+    /// * leases: `contract_id`, `lease_id`, `recipient`, `amount: i64`
+    /// * cancel leases: `contract_id`, `lease_id`
+    /// * payments: `contract_id`, `payment_id`, payments serialized as in `Payments::as_bytes`
+    ///
+    /// Followed by `fuel_consumed: u64` and the calls: `contract_id`, `func_name`,
+    /// `depth: u32`, `fuel_consumed: u64`.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+
+        put_u32(&mut bytes, self.storage.len());
+        for item in self.storage.iter() {
+            put_bytes(&mut bytes, &item.contract_id);
+            bytes.extend(item.value.serialize(Some(&item.key)));
+        }
+
+        put_u32(&mut bytes, self.transfers.len());
+        for item in self.transfers.iter() {
+            put_bytes(&mut bytes, &item.contract_id);
+            put_bytes(&mut bytes, &item.asset_id);
+            put_bytes(&mut bytes, &item.recipient);
+            bytes.extend_from_slice(&item.amount.to_be_bytes());
+        }
+
+        put_u32(&mut bytes, self.issues.len());
+        for item in self.issues.iter() {
+            put_bytes(&mut bytes, &item.contract_id);
+            put_bytes(&mut bytes, &item.asset_id);
+            put_bytes(&mut bytes, &item.name);
+            put_bytes(&mut bytes, &item.description);
+            bytes.extend_from_slice(&item.quantity.to_be_bytes());
+            bytes.extend_from_slice(&item.decimals.to_be_bytes());
+            bytes.push(item.is_reissuable as u8);
+        }
+
+        put_u32(&mut bytes, self.burns.len());
+        for item in self.burns.iter() {
+            put_bytes(&mut bytes, &item.contract_id);
+            put_bytes(&mut bytes, &item.asset_id);
+            bytes.extend_from_slice(&item.amount.to_be_bytes());
+        }
+
+        put_u32(&mut bytes, self.reissues.len());
+        for item in self.reissues.iter() {
+            put_bytes(&mut bytes, &item.contract_id);
+            put_bytes(&mut bytes, &item.asset_id);
+            bytes.extend_from_slice(&item.amount.to_be_bytes());
+            bytes.push(item.is_reissuable as u8);
+        }
+
+        put_u32(&mut bytes, self.leases.len());
+        for item in self.leases.iter() {
+            put_bytes(&mut bytes, &item.contract_id);
+            put_bytes(&mut bytes, &item.lease_id);
+            put_bytes(&mut bytes, &item.recipient);
+            bytes.extend_from_slice(&item.amount.to_be_bytes());
+        }
+
+        put_u32(&mut bytes, self.cancel_leases.len());
+        for item in self.cancel_leases.iter() {
+            put_bytes(&mut bytes, &item.contract_id);
+            put_bytes(&mut bytes, &item.lease_id);
+        }
+
+        put_u32(&mut bytes, self.payments.len());
+        for item in self.payments.iter() {
+            put_bytes(&mut bytes, &item.contract_id);
+            put_bytes(&mut bytes, &item.payment_id);
+            bytes.extend(item.payments.as_bytes());
+        }
+
+        bytes.extend_from_slice(&self.fuel_consumed.to_be_bytes());
+
+        put_u32(&mut bytes, self.calls.len());
+        for item in self.calls.iter() {
+            put_bytes(&mut bytes, &item.contract_id);
+            put_bytes(&mut bytes, item.func_name.as_bytes());
+            put_u32(&mut bytes, item.depth);
+            bytes.extend_from_slice(&item.fuel_consumed.to_be_bytes());
+        }
+
+        bytes
+    }
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend_from_slice(&(value as u32).to_be_bytes());
+}
+
+fn put_bytes(bytes: &mut Vec<u8>, value: &[u8]) {
+    put_u32(bytes, value.len());
+    bytes.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execution_result() {
+        let operations = vec![
+            Operation::SetStorage {
+                contract_id: vec![1],
+                value: DataEntry::Boolean(1).serialize(Some(b"key")),
+            },
+            Operation::Burn {
+                contract_id: vec![1],
+                asset_id: vec![2],
+                amount: 3,
+            },
+        ];
+
+        let result = ExecutionResult::new(&operations, 42, &[]).expect("Error execution result");
+        assert_eq!(
+            result.storage,
+            vec![StorageWrite {
+                contract_id: vec![1],
+                key: b"key".to_vec(),
+                value: DataEntry::Boolean(1),
+            }]
+        );
+        assert_eq!(result.burns.len(), 1);
+
+        let mut bytes = vec![0, 0, 0, 1, 0, 0, 0, 1, 1, 0, 3, b'k', b'e', b'y', 1, 1];
+        bytes.extend([0, 0, 0, 0, 0, 0, 0, 0]);
+        bytes.extend([
+            0, 0, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 1, 2, 0, 0, 0, 0, 0, 0, 0, 3,
+        ]);
+        bytes.extend([0; 16]);
+        bytes.extend([0, 0, 0, 0, 0, 0, 0, 42, 0, 0, 0, 0]);
+        assert_eq!(result.as_bytes(), bytes);
+    }
+}
@@ -0,0 +1,598 @@
+use crate::{
+    error::{Error, ExecutableError, Result, RuntimeError},
+    node::Node,
+    runtime::{
+        asset_holder::{AddressVersion, AssetHolder, Type},
+        data_entry::DataEntry,
+        payments::Payments,
+        utils,
+    },
+};
+use std::collections::HashMap;
+
+/// Length of the nonce at the end of the payment identifier.
+const NONCE_LENGTH: usize = 8;
+
+/// Balance of an `AssetHolder` as `(asset_id, AssetHolder)`.
+pub type BalanceKey = (Vec<u8>, Vec<u8>);
+
+/// Change of a balance as `(asset_id, AssetHolder, amount)`.
+pub type BalanceChange = (Vec<u8>, Vec<u8>, i64);
+
+/// State-changing call to the node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    SetStorage {
+        contract_id: Vec<u8>,
+        value: Vec<u8>,
+    },
+    DeleteStorage {
+        contract_id: Vec<u8>,
+        key: Vec<u8>,
+    },
+    Transfer {
+        contract_id: Vec<u8>,
+        asset_id: Vec<u8>,
+        recipient: Vec<u8>,
+        amount: i64,
+    },
+    /// Issue is applied by the node at once, since the node assigns `asset_id`,
+    /// and is compensated by burning the quantity if the call fails.
+    Issue {
+        contract_id: Vec<u8>,
+        asset_id: Vec<u8>,
+        name: Vec<u8>,
+        description: Vec<u8>,
+        quantity: i64,
+        decimals: i64,
+        is_reissuable: bool,
+    },
+    Burn {
+        contract_id: Vec<u8>,
+        asset_id: Vec<u8>,
+        amount: i64,
+    },
+    Reissue {
+        contract_id: Vec<u8>,
+        asset_id: Vec<u8>,
+        amount: i64,
+        is_reissuable: bool,
+    },
+    /// Lease is applied by the node at once, since the node assigns `lease_id`,
+    /// and is compensated by canceling the lease if the call fails.
+    Lease {
+        contract_id: Vec<u8>,
+        lease_id: Vec<u8>,
+        recipient: Vec<u8>,
+        amount: i64,
+    },
+    CancelLease {
+        contract_id: Vec<u8>,
+        lease_id: Vec<u8>,
+    },
+    AddPayments {
+        contract_id: Vec<u8>,
+        payment_id: Vec<u8>,
+        payments: Vec<u8>,
+    },
+}
+
+impl Operation {
+    /// The contract on whose behalf the operation is made.
+    pub fn contract_id(&self) -> &[u8] {
+        match self {
+            Self::SetStorage { contract_id, .. }
+            | Self::DeleteStorage { contract_id, .. }
+            | Self::Transfer { contract_id, .. }
+            | Self::Issue { contract_id, .. }
+            | Self::Burn { contract_id, .. }
+            | Self::Reissue { contract_id, .. }
+            | Self::Lease { contract_id, .. }
+            | Self::CancelLease { contract_id, .. }
+            | Self::AddPayments { contract_id, .. } => contract_id,
+        }
+    }
+
+    /// The operation is applied by the node as soon as it is made.
+    pub fn is_applied_at_once(&self) -> bool {
+        matches!(self, Self::Issue { .. } | Self::Lease { .. })
+    }
+
+    /// Operation that reverts an operation applied at once.
+    pub fn compensation(&self) -> Option<Operation> {
+        match self {
+            Self::Issue {
+                contract_id,
+                asset_id,
+                quantity,
+                ..
+            } => Some(Self::Burn {
+                contract_id: contract_id.clone(),
+                asset_id: asset_id.clone(),
+                amount: *quantity,
+            }),
+            Self::Lease {
+                contract_id,
+                lease_id,
+                ..
+            } => Some(Self::CancelLease {
+                contract_id: contract_id.clone(),
+                lease_id: lease_id.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Changes of balances made by the operation as `(asset_id, AssetHolder, amount)`.
+    pub fn balance_changes(&self) -> Result<Vec<BalanceChange>> {
+        match self {
+            Self::Transfer {
+                contract_id,
+                asset_id,
+                recipient,
+                amount,
+            } => Ok(vec![
+                (asset_id.clone(), Journal::contract(contract_id), -amount),
+                (asset_id.clone(), recipient.clone(), *amount),
+            ]),
+            Self::Burn {
+                contract_id,
+                asset_id,
+                amount,
+            } => Ok(vec![(
+                asset_id.clone(),
+                Journal::contract(contract_id),
+                -amount,
+            )]),
+            Self::Reissue {
+                contract_id,
+                asset_id,
+                amount,
+                ..
+            } => Ok(vec![(
+                asset_id.clone(),
+                Journal::contract(contract_id),
+                *amount,
+            )]),
+            Self::AddPayments {
+                contract_id,
+                payment_id,
+                ..
+            } => {
+                let callable_contract_id = match payment_id.len().checked_sub(NONCE_LENGTH) {
+                    Some(length) => &payment_id[..length],
+                    None => return Err(Error::Executable(ExecutableError::FailedDeserialize)),
+                };
+
+                let mut result = vec![];
+                for (asset_id, amount) in self.debits()? {
+                    result.push((asset_id.clone(), Journal::contract(contract_id), -amount));
+                    result.push((asset_id, Journal::contract(callable_contract_id), amount));
+                }
+
+                Ok(result)
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Funds withdrawn from the balance of the contract as `(asset_id, amount)`.
+    pub fn debits(&self) -> Result<Vec<(Vec<u8>, i64)>> {
+        match self {
+            Self::Transfer {
+                asset_id, amount, ..
+            }
+            | Self::Burn {
+                asset_id, amount, ..
+            } => Ok(vec![(asset_id.clone(), *amount)]),
+            Self::AddPayments { payments, .. } => Ok(Payments::deserialize(payments)?.0),
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Applying the operation to the node.
+    pub fn apply(&self, node: &dyn Node) -> Result<()> {
+        match self {
+            Self::SetStorage { contract_id, value } => node.set_storage(contract_id, value),
+            Self::DeleteStorage { contract_id, key } => node.delete_storage(contract_id, key),
+            Self::Transfer {
+                contract_id,
+                asset_id,
+                recipient,
+                amount,
+            } => node.transfer(contract_id, asset_id, recipient, *amount),
+            Self::Burn {
+                contract_id,
+                asset_id,
+                amount,
+            } => node.burn(contract_id, asset_id, *amount),
+            Self::Reissue {
+                contract_id,
+                asset_id,
+                amount,
+                is_reissuable,
+            } => node.reissue(contract_id, asset_id, *amount, *is_reissuable),
+            Self::Issue { .. } | Self::Lease { .. } => Ok(()),
+            Self::CancelLease {
+                contract_id,
+                lease_id,
+            } => node.cancel_lease(contract_id, lease_id),
+            Self::AddPayments {
+                contract_id,
+                payment_id,
+                payments,
+            } => node.add_payments(contract_id, payment_id, payments),
+        }
+    }
+}
+
+/// State changes of a frame of the call stack that are not yet applied to the node.
+///
+/// Besides the operations in the order of calls, the journal keeps their effect
+/// on storage, balances and payments, so the contracts read their own writes.
+#[derive(Debug, Default)]
+pub struct Journal {
+    operations: Vec<Operation>,
+    // (address, key) -> serialized DataEntry, empty for a deleted key
+    storage: HashMap<(Vec<u8>, Vec<u8>), Vec<u8>>,
+    // (asset_id, AssetHolder) -> change of the balance
+    balances: HashMap<BalanceKey, i64>,
+    // payment_id -> [(asset_id, amount)]
+    payments: HashMap<Vec<u8>, Vec<(Vec<u8>, i64)>>,
+}
+
+impl Journal {
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Getting the value written under the key, the value is empty if the key is deleted.
+    pub fn storage(&self, address: &[u8], key: &[u8]) -> Option<&Vec<u8>> {
+        self.storage.get(&(address.to_vec(), key.to_vec()))
+    }
+
+    /// Getting the keys written to the storage of the address with their values,
+    /// the value is empty if the key is deleted.
+    pub fn storage_entries<'a>(
+        &'a self,
+        address: &'a [u8],
+    ) -> impl Iterator<Item = (&'a Vec<u8>, &'a Vec<u8>)> + 'a {
+        self.storage
+            .iter()
+            .filter(move |((id, _), _)| id == address)
+            .map(|((_, key), value)| (key, value))
+    }
+
+    /// Getting the change of the balance of the `AssetHolder`.
+    pub fn balance(&self, asset_id: &[u8], holder: &[u8]) -> i64 {
+        *self
+            .balances
+            .get(&(asset_id.to_vec(), holder.to_vec()))
+            .unwrap_or(&0)
+    }
+
+    /// Getting the payments attached to the call.
+    pub fn payments(&self, payment_id: &[u8]) -> Option<&Vec<(Vec<u8>, i64)>> {
+        self.payments.get(payment_id)
+    }
+
+    /// Recording the operation together with its effect.
+    pub fn push(&mut self, operation: Operation) -> Result<()> {
+        match &operation {
+            Operation::SetStorage { contract_id, value } => {
+                let mut offset = 0;
+                let length = utils::get_u16(value, &mut offset)?;
+                let key = utils::get_bytes(value, &mut offset, length as usize)?;
+
+                self.storage
+                    .insert((contract_id.clone(), key), value.clone());
+            }
+            Operation::DeleteStorage { contract_id, key } => {
+                self.storage
+                    .insert((contract_id.clone(), key.clone()), vec![]);
+            }
+            Operation::AddPayments { payment_id, .. } => {
+                let payments = operation.debits()?;
+                self.payments
+                    .entry(payment_id.clone())
+                    .or_default()
+                    .extend(payments);
+            }
+            _ => (),
+        }
+
+        for (asset_id, holder, amount) in operation.balance_changes()? {
+            self.add_balance(&asset_id, &holder, amount);
+        }
+
+        self.operations.push(operation);
+        Ok(())
+    }
+
+    /// Moving the changes of a completed call into the journal of the caller.
+    pub fn merge(&mut self, journal: Journal) {
+        self.operations.extend(journal.operations);
+        self.storage.extend(journal.storage);
+
+        for ((asset_id, holder), amount) in journal.balances {
+            self.add_balance(&asset_id, &holder, amount);
+        }
+
+        for (payment_id, payments) in journal.payments {
+            self.payments
+                .entry(payment_id)
+                .or_default()
+                .extend(payments);
+        }
+    }
+
+    /// Checking that the balances of the node cover the withdrawals of all operations
+    /// in the order they were made, so the node does not reject them halfway through the commit.
+    pub fn check_funds<F>(&self, balance: F) -> Result<()>
+    where
+        F: Fn(&[u8], &[u8]) -> Result<i64>,
+    {
+        let mut balances: HashMap<BalanceKey, i64> = HashMap::new();
+
+        for operation in self.operations.iter() {
+            let changes = operation.balance_changes()?;
+
+            for (asset_id, holder, amount) in changes.iter() {
+                let key = (asset_id.clone(), holder.clone());
+                let current = match balances.get(&key) {
+                    Some(value) => *value,
+                    None => balance(asset_id, holder)?,
+                };
+
+                if *amount < 0 && current.saturating_add(*amount) < 0 {
+                    return Err(Error::Runtime(RuntimeError::NodeRejected(format!(
+                        "Insufficient funds: {} < {}",
+                        current, -amount
+                    ))));
+                }
+
+                balances.insert(key, current.saturating_add(*amount));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applying all operations to the node in the order they were made.
+    /// Consecutive storage writes of a contract are passed to the node in a single batch.
+    ///
+    /// Funds are to be checked with [`Journal::check_funds`] beforehand. If the node
+    /// still rejects an operation, the commit stops and the operations applied before it
+    /// remain applied: they are listed in `applied`, and the node is expected to discard
+    /// the changes of the whole transaction on the returned error.
+    pub fn commit(self, node: &dyn Node, applied: &mut Vec<Operation>) -> Result<()> {
+        let mut index = 0;
+
+        while index < self.operations.len() {
+            let batch = self.storage_batch(index);
+            let count = batch.len().max(1);
+
+            match batch.as_slice() {
+                [] | [_] => self.operations[index].apply(node)?,
+                values => {
+                    let contract_id = self.operations[index].contract_id();
+                    node.set_storage_batch(contract_id, &DataEntry::join_batch(values))?;
+                }
+            }
+
+            // Operations applied at once are already listed
+            applied.extend(
+                self.operations[index..index + count]
+                    .iter()
+                    .filter(|operation| !operation.is_applied_at_once())
+                    .cloned(),
+            );
+            index += count;
+        }
+
+        Ok(())
+    }
+
+    /// Reverting the operations applied at once by a failed call, the compensating
+    /// operations are applied to the node in the reverse order and listed in `applied`.
+    pub fn rollback(self, node: &dyn Node, applied: &mut Vec<Operation>) -> Result<()> {
+        for operation in self.operations.iter().rev() {
+            if let Some(compensation) = operation.compensation() {
+                compensation.apply(node)?;
+                applied.push(compensation);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Getting consecutive storage writes of a contract starting from the operation.
+    fn storage_batch(&self, index: usize) -> Vec<Vec<u8>> {
+        let contract_id = self.operations[index].contract_id();
+
+        self.operations[index..]
+            .iter()
+            .map_while(|operation| match operation {
+                Operation::SetStorage {
+                    contract_id: id,
+                    value,
+                } if id == contract_id => Some(value.clone()),
+                _ => None,
+            })
+            .take(u16::MAX as usize)
+            .collect()
+    }
+
+    /// `AssetHolder` of the contract.
+    pub fn contract(contract_id: &[u8]) -> Vec<u8> {
+        AssetHolder::from_bytes(
+            Type::Contract,
+            AddressVersion::Address,
+            0,
+            contract_id.to_vec(),
+        )
+        .as_bytes()
+    }
+
+    fn add_balance(&mut self, asset_id: &[u8], holder: &[u8], amount: i64) {
+        let balance = self
+            .balances
+            .entry((asset_id.to_vec(), holder.to_vec()))
+            .or_default();
+        *balance = balance.saturating_add(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::ledger::Ledger;
+
+    #[test]
+    fn test_journal() {
+        let ledger = Ledger::new(b'T' as i8);
+        ledger.set_balance(&[], b"contract", 100);
+
+        let mut journal = Journal::default();
+        journal
+            .push(Operation::SetStorage {
+                contract_id: b"contract".to_vec(),
+                value: DataEntry::Integer(1).serialize(Some(b"key")),
+            })
+            .expect("Error push operation");
+
+        let mut nested = Journal::default();
+        nested
+            .push(Operation::SetStorage {
+                contract_id: b"contract".to_vec(),
+                value: DataEntry::Integer(2).serialize(Some(b"key")),
+            })
+            .expect("Error push operation");
+        nested
+            .push(Operation::Transfer {
+                contract_id: b"contract".to_vec(),
+                asset_id: vec![],
+                recipient: b"\x00recipient".to_vec(),
+                amount: 30,
+            })
+            .expect("Error push operation");
+
+        let holder = Journal::contract(b"contract");
+        assert_eq!(nested.balance(&[], &holder), -30);
+        assert_eq!(nested.balance(&[], b"\x00recipient"), 30);
+
+        journal.merge(nested);
+        assert_eq!(journal.operations().len(), 3);
+        assert_eq!(
+            journal.storage(b"contract", b"key"),
+            Some(&DataEntry::Integer(2).serialize(Some(b"key")))
+        );
+        assert_eq!(journal.balance(&[], &holder), -30);
+
+        // Nothing is applied before the commit
+        assert_eq!(ledger.storage(b"contract", b"key"), None);
+
+        let mut applied = vec![];
+        journal.commit(&ledger, &mut applied).expect("Error commit");
+        assert_eq!(applied.len(), 3);
+        assert_eq!(
+            ledger.storage(b"contract", b"key"),
+            Some(DataEntry::Integer(2))
+        );
+        assert_eq!(ledger.balance(&[], b"contract"), 70);
+        assert_eq!(ledger.balance(&[], b"recipient"), 30);
+    }
+
+    #[test]
+    fn test_journal_commit_failure() {
+        let ledger = Ledger::new(b'T' as i8);
+        ledger.set_balance(&[], b"contract", 100);
+
+        let transfer = |amount| Operation::Transfer {
+            contract_id: b"contract".to_vec(),
+            asset_id: vec![],
+            recipient: b"\x00recipient".to_vec(),
+            amount,
+        };
+        let balance = |asset_id: &[u8], holder: &[u8]| ledger.get_balance(asset_id, holder);
+
+        let mut journal = Journal::default();
+        journal.push(transfer(60)).expect("Error push operation");
+        assert_eq!(journal.check_funds(balance), Ok(()));
+
+        // The withdrawals are checked together before anything is applied
+        journal.push(transfer(60)).expect("Error push operation");
+        assert!(journal.check_funds(balance).is_err());
+        assert_eq!(ledger.balance(&[], b"contract"), 100);
+
+        // An operation rejected by the node stops the commit,
+        // the operations applied before it are listed
+        let mut journal = Journal::default();
+        journal
+            .push(Operation::SetStorage {
+                contract_id: b"contract".to_vec(),
+                value: DataEntry::Integer(1).serialize(Some(b"key")),
+            })
+            .expect("Error push operation");
+        journal
+            .push(Operation::CancelLease {
+                contract_id: b"contract".to_vec(),
+                lease_id: b"unknown".to_vec(),
+            })
+            .expect("Error push operation");
+        journal.push(transfer(10)).expect("Error push operation");
+        assert_eq!(journal.check_funds(balance), Ok(()));
+
+        let mut applied = vec![];
+        assert!(journal.commit(&ledger, &mut applied).is_err());
+        assert_eq!(applied.len(), 1);
+        assert_eq!(
+            ledger.storage(b"contract", b"key"),
+            Some(DataEntry::Integer(1))
+        );
+        assert_eq!(ledger.balance(&[], b"contract"), 100);
+    }
+
+    #[test]
+    fn test_journal_rollback() {
+        let ledger = Ledger::new(b'T' as i8);
+
+        let asset_id = ledger
+            .issue(b"contract", b"name", b"", 1000, 0, false)
+            .expect("Error issue");
+        let lease_id = ledger
+            .lease(b"contract", b"\x00recipient", 10)
+            .expect("Error lease");
+
+        let mut journal = Journal::default();
+        for operation in [
+            Operation::Issue {
+                contract_id: b"contract".to_vec(),
+                asset_id: asset_id.clone(),
+                name: b"name".to_vec(),
+                description: vec![],
+                quantity: 1000,
+                decimals: 0,
+                is_reissuable: false,
+            },
+            Operation::Lease {
+                contract_id: b"contract".to_vec(),
+                lease_id: lease_id.clone(),
+                recipient: b"\x00recipient".to_vec(),
+                amount: 10,
+            },
+        ] {
+            journal.push(operation).expect("Error push operation");
+        }
+
+        let mut applied = vec![];
+        journal
+            .rollback(&ledger, &mut applied)
+            .expect("Error rollback");
+
+        assert_eq!(applied.len(), 2);
+        assert!(matches!(applied[0], Operation::CancelLease { .. }));
+        assert_eq!(ledger.balance(&asset_id, b"contract"), 0);
+        assert_eq!(ledger.lease_info(&lease_id), None);
+    }
+}
@@ -18,6 +18,11 @@ class Simulator(bytecode: Array[Byte]) {
   def fuelLimit: Long           = this._fuelLimit
   def setFuelLimit(value: Long) = this._fuelLimit = value
 
+  private var _config: VmConfig = VmConfig()
+
+  def config: VmConfig           = this._config
+  def setConfig(value: VmConfig) = this._config = value
+
   def chainId: Byte           = this.service.getChainId()
   def setChainId(value: Byte) = this.service.setChainId(value)
 
@@ -28,6 +33,16 @@ class Simulator(bytecode: Array[Byte]) {
 
   def height: Long = this.service.height
 
+  /**
+    * Fuel consumed by frames of the last call: function name, depth and fuel
+    */
+  def fuelConsumed: Seq[(String, Int, Long)] = this.service.fuelConsumed.toSeq
+
+  /**
+    * Error of the last call if it failed: code, category and message
+    */
+  def lastError: Option[(Int, String, String)] = this.service.lastError
+
   private val _accounts: Array[Array[Byte]] = generateAccounts(Array.empty[Array[Byte]])
 
   def accounts(i: Int): Array[Byte] =
@@ -55,7 +70,14 @@ class Simulator(bytecode: Array[Byte]) {
 
   def callMethod(funcName: String, params: Array[Byte]): Int = {
     this.service.setTxSender(this._accounts(0))
-    this.executor.runContract(this.contractId, this.bytecode, funcName, params, this.fuelLimit, this.service)
+    this.service.resetFuelConsumed()
+    this.service.resetLastError()
+    this.executor.runContract(this.contractId, this.bytecode, funcName, params, this.fuelLimit, this.config.bytes, this.service)
+  }
+
+  def validateBytecode(): Int = {
+    this.service.resetLastError()
+    this.executor.validateBytecode(this.bytecode, this.config.bytes, this.service)
   }
 
   def getBalance(assetId: Array[Byte], assetHolder: Array[Byte]): Long =
@@ -9,6 +9,7 @@ import com.wavesenterprise.transaction.docker.ContractTransactionEntryOps.{parse
 import java.nio.ByteBuffer
 import java.nio.charset.StandardCharsets.UTF_8
 import java.time.Instant
+import java.util.Arrays
 
 import scala.collection.mutable.{Map, Seq}
 
@@ -24,6 +25,8 @@ class SimulatorWASMService extends WASMService {
   private val _leases: Map[ByteBuffer, (ByteBuffer, Long)]         = Map.empty[ByteBuffer, (ByteBuffer, Long)]
   private val _storage: Map[ByteBuffer, Map[String, DataEntry[_]]] = Map.empty[ByteBuffer, Map[String, DataEntry[_]]]
   private val _payments: Map[ByteBuffer, Seq[(ByteBuffer, Long)]]  = Map.empty[ByteBuffer, Seq[(ByteBuffer, Long)]]
+  private var _fuelConsumed: Seq[(String, Int, Long)]              = Seq.empty[(String, Int, Long)]
+  private var _lastError: Option[(Int, String, String)]            = None
 
   private[core] def setChainId(value: Byte) = this._chainId = value
 
@@ -34,6 +37,12 @@ class SimulatorWASMService extends WASMService {
 
   private[core] def height: Long = this._height
 
+  private[core] def fuelConsumed: Seq[(String, Int, Long)] = this._fuelConsumed
+  private[core] def resetFuelConsumed()                     = this._fuelConsumed = Seq.empty[(String, Int, Long)]
+
+  private[core] def lastError: Option[(Int, String, String)] = this._lastError
+  private[core] def resetLastError()                         = this._lastError = None
+
   private[core] def setTxSender(value: Array[Byte]) = this._txSender = value
 
   private[core] def setBytecode(contractId: ByteBuffer, bytecode: Array[Byte]) =
@@ -239,6 +248,27 @@ class SimulatorWASMService extends WASMService {
     this._storage(wContractId) = kv
   }
 
+  override def deleteStorage(contractId: Array[Byte], key: Array[Byte]) = {
+    val k = if (key.isEmpty) throw new Exception else new String(key)
+    this.getKeyValueStorage(ByteBuffer.wrap(contractId)).remove(k)
+  }
+
+  override def storageKeys(contractId: Array[Byte], prefix: Array[Byte], cursor: Array[Byte], limit: Int): Array[Byte] = {
+    val keys = this
+      .getKeyValueStorage(ByteBuffer.wrap(contractId))
+      .keys
+      .map(_.getBytes(UTF_8))
+      .filter(key => key.startsWith(prefix) && Arrays.compareUnsigned(key, cursor) > 0)
+      .toSeq
+      .sortWith((left, right) => Arrays.compareUnsigned(left, right) < 0)
+      .take(limit)
+
+    val buffer = ByteBuffer.allocate(2 + keys.map(_.length + 2).sum)
+    buffer.putShort(keys.length.toShort)
+    keys.foreach(key => buffer.putShort(key.length.toShort).put(key))
+    buffer.array
+  }
+
   override def getTxPayments(paymentId: Array[Byte]): Long =
     this._payments.getOrElse(ByteBuffer.wrap(paymentId), Seq.empty[(ByteBuffer, Long)]).size
 
@@ -259,4 +289,10 @@ class SimulatorWASMService extends WASMService {
       case "sender" => this._txSender
       case _        => throw new Exception
     }
+
+  override def fuelConsumed(contractId: Array[Byte], funcName: String, depth: Int, fuel: Long): Unit =
+    this._fuelConsumed = this._fuelConsumed :+ ((funcName, depth, fuel))
+
+  override def executionFailed(code: Int, category: String, message: String, contractId: Array[Byte], frames: Array[Byte]): Unit =
+    this._lastError = Some((code, category, message))
 }
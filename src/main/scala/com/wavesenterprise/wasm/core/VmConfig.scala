@@ -0,0 +1,86 @@
+package com.wavesenterprise.wasm.core
+
+import java.nio.ByteBuffer
+import java.nio.charset.StandardCharsets
+
+/**
+  * Fuel cost of a host function, overriding the default one
+  * @param module Module of the host function, e.g. `env0`
+  * @param name Name of the host function
+  * @param base Fuel charged for each call
+  * @param perByte Fuel charged for each byte of the data passed to the call and written to memory by it
+  * @param perEntry Fuel charged for each entry requested from the node by the call
+  */
+case class HostFunctionCost(module: String, name: String, base: Long, perByte: Long, perEntry: Long = 0L)
+
+/**
+  * Execution limits passed with each call to the executor
+  * @param memoryInitial Initial number of memory pages of a contract
+  * @param memoryMaximum Maximum number of memory pages of a contract
+  * @param maxCallDepth Maximum number of contracts in the call stack
+  * @param initialValueStackHeight Initial height of the Wasm value stack
+  * @param maximumValueStackHeight Maximum height of the Wasm value stack
+  * @param maximumRecursionDepth Maximum number of nested calls of functions within a contract
+  * @param maxBytecodeSize Maximum size of the bytecode of a contract in bytes
+  * @param maxParamsSize Maximum size of the serialized function arguments in bytes
+  * @param lintMaxMemoryPages Maximum number of memory pages declared by the bytecode
+  * @param lintMaxTableSize Maximum number of elements of a table declared by the bytecode
+  * @param lintMaxDataSegmentSize Maximum size of a data segment in bytes
+  * @param lintMaxLocals Maximum number of locals of a function
+  * @param lintMaxFunctions Maximum number of functions defined by the bytecode
+  * @param timeoutMillis Wall-clock time limit of a call, 0 disables it
+  * @param costs Costs of host functions overriding the default ones, an unknown function is rejected
+  */
+case class VmConfig(
+    memoryInitial: Int = 2,
+    memoryMaximum: Int = 16,
+    maxCallDepth: Int = 64,
+    initialValueStackHeight: Int = 128,
+    maximumValueStackHeight: Int = 128 * 1024,
+    maximumRecursionDepth: Int = 1024,
+    maxBytecodeSize: Int = 4 * 1024 * 1024,
+    maxParamsSize: Int = 1024 * 1024,
+    lintMaxMemoryPages: Int = 16,
+    lintMaxTableSize: Int = 1024,
+    lintMaxDataSegmentSize: Int = 64 * 1024,
+    lintMaxLocals: Int = 1024,
+    lintMaxFunctions: Int = 10000,
+    timeoutMillis: Long = 0L,
+    costs: Seq[HostFunctionCost] = Seq.empty
+) {
+  def bytes: Array[Byte] = {
+    val names = costs.map { cost =>
+      (cost.module.getBytes(StandardCharsets.UTF_8), cost.name.getBytes(StandardCharsets.UTF_8))
+    }
+    val buffer = ByteBuffer
+      .allocate(13 * 4 + 8 + 2 + names.map { case (module, name) => 2 + module.length + 2 + name.length + 3 * 8 }.sum)
+      .putInt(memoryInitial)
+      .putInt(memoryMaximum)
+      .putInt(maxCallDepth)
+      .putInt(initialValueStackHeight)
+      .putInt(maximumValueStackHeight)
+      .putInt(maximumRecursionDepth)
+      .putInt(maxBytecodeSize)
+      .putInt(maxParamsSize)
+      .putInt(lintMaxMemoryPages)
+      .putInt(lintMaxTableSize)
+      .putInt(lintMaxDataSegmentSize)
+      .putInt(lintMaxLocals)
+      .putInt(lintMaxFunctions)
+      .putLong(timeoutMillis)
+      .putShort(costs.length.toShort)
+
+    costs.zip(names).foreach { case (cost, (module, name)) =>
+      buffer
+        .putShort(module.length.toShort)
+        .put(module)
+        .putShort(name.length.toShort)
+        .put(name)
+        .putLong(cost.base)
+        .putLong(cost.perByte)
+        .putLong(cost.perEntry)
+    }
+
+    buffer.array()
+  }
+}
@@ -3,14 +3,65 @@ package com.wavesenterprise.wasm.core
 import com.github.sbt.jni.syntax.NativeLoader
 
 class WASMExecutor extends NativeLoader("wevm") {
+
+  /**
+    * Executes the contract
+    * @param config Serialized VmConfig, an empty array for the default limits
+    * @return Error code, an invalid config fails with the code 121
+    */
   @native def runContract(
       contractId: Array[Byte],
       bytecode: Array[Byte],
       funcName: String,
       params: Array[Byte],
       fuelLimit: Long,
+      config: Array[Byte],
+      callback: WASMService
+  ): Int
+
+  /**
+    * Executes the contract without the right to change the state
+    * @return Error code, state changes fail with the code 312
+    */
+  @native def runContractReadOnly(
+      contractId: Array[Byte],
+      bytecode: Array[Byte],
+      funcName: String,
+      params: Array[Byte],
+      fuelLimit: Long,
+      config: Array[Byte],
       callback: WASMService
   ): Int
 
-  @native def validateBytecode(bytecode: Array[Byte]): Int
+  /**
+    * @return Error code as big-endian Int, followed by the serialized execution result
+    *         (state changes, fuel consumed and calls) if the code is 0
+    */
+  @native def runContractWithResult(
+      contractId: Array[Byte],
+      bytecode: Array[Byte],
+      funcName: String,
+      params: Array[Byte],
+      fuelLimit: Long,
+      config: Array[Byte],
+      callback: WASMService
+  ): Array[Byte]
+
+  /**
+    * @param config Serialized VmConfig, an empty array for the default limits
+    * @param callback Receives the failure with executionFailed, invalid imports are listed in the message
+    * @return Error code
+    */
+  @native def validateBytecode(bytecode: Array[Byte], config: Array[Byte], callback: WASMService): Int
+
+  /**
+    * Checks the bytecode for non-deterministic constructs and the lint limits of the config
+    * @param config Serialized VmConfig, an empty array for the default limits
+    * @param callback Receives the failure with executionFailed
+    * @return Error code as big-endian Int, followed by the report if the code is 0:
+    *         number of issues as 2 bytes, each issue given by severity (0 - warning, 1 - error),
+    *         kind as 1 byte and the message prefixed with the length as 2 bytes.
+    *         An invalid config fails with the code 121
+    */
+  @native def lintBytecode(bytecode: Array[Byte], config: Array[Byte], callback: WASMService): Array[Byte]
 }
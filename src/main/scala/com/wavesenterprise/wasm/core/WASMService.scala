@@ -1,5 +1,7 @@
 package com.wavesenterprise.wasm.core
 
+import com.wavesenterprise.transaction.docker.ContractTransactionEntryOps.parse
+
 trait WASMService {
 
   /**
@@ -126,6 +128,37 @@ trait WASMService {
     */
   def setStorage(contractId: Array[Byte], value: Array[Byte]): Unit
 
+  /**
+    * @param contractId ID of a contract called this function. Base58 bytes
+    * @param values Number of records as 2 bytes followed by serialized DataEntry records
+    */
+  def setStorageBatch(contractId: Array[Byte], values: Array[Byte]): Unit = {
+    val count = ((values(0) & 0xff) << 8) | (values(1) & 0xff)
+
+    (0 until count).foldLeft(2) { (offset, _) =>
+      val next = parse(values, offset)._2
+      setStorage(contractId, values.slice(offset, next))
+      next
+    }
+  }
+
+  /**
+    * @param contractId ID of a contract called this function. Base58 bytes
+    * @param key Key of the record to be deleted
+    */
+  def deleteStorage(contractId: Array[Byte], key: Array[Byte]): Unit =
+    throw new UnsupportedOperationException("Storage deletion is not supported")
+
+  /**
+    * @param contractId ID of a contract. Base58 bytes
+    * @param prefix Prefix of the keys
+    * @param cursor Keys are returned after this key, from the beginning if empty
+    * @param limit Maximum number of keys
+    * @return Keys in ascending byte order: number of keys as 2 bytes, each key prefixed with 2 bytes of length
+    */
+  def storageKeys(contractId: Array[Byte], prefix: Array[Byte], cursor: Array[Byte], limit: Int): Array[Byte] =
+    throw new UnsupportedOperationException("Storage key iteration is not supported")
+
   /**
     * @param paymentId Unique payment identifier. Represents the concatenation of contractId bytes and unique 8 bytes
     * @return Number of attached payments
@@ -151,4 +184,26 @@ trait WASMService {
     * @return Requested field data
     */
   def tx(field: Array[Byte]): Array[Byte]
+
+  /**
+    * Called after the execution for each frame of the call stack in the order of calls
+    * @param contractId ID of a contract. Base58 bytes
+    * @param funcName Name of the called function
+    * @param depth Depth of the frame in the call stack, 0 for the top-level call
+    * @param fuel Fuel consumed by the frame including nested calls
+    */
+  def fuelConsumed(contractId: Array[Byte], funcName: String, depth: Int, fuel: Long): Unit = ()
+
+  /**
+    * Called after a failed execution or check of the bytecode, before the error code is returned
+    * @param code Error code returned by the execution
+    * @param category Executable, Jvm, Runtime or Contract for a code returned by the contract
+    * @param message Error message, or the message passed to require for a contract error.
+    *                Invalid imports (code 117) are given as "module.name: reason" separated by "; "
+    * @param contractId ID of a contract where the error occurred, empty for a check. Base58 bytes
+    * @param frames Call stack from the top-level call to the contract where the error occurred:
+    *               number of frames as 2 bytes, each frame given by the contract ID
+    *               and the function name prefixed with the length as 2 bytes
+    */
+  def executionFailed(code: Int, category: String, message: String, contractId: Array[Byte], frames: Array[Byte]): Unit = ()
 }
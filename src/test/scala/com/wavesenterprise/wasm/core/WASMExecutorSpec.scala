@@ -3,18 +3,54 @@ package com.wavesenterprise.wasm.core
 import org.scalatest.freespec.AnyFreeSpec
 import org.scalatest.matchers.should.Matchers
 
+import java.nio.ByteBuffer
+
 class WASMExecutorSpec extends AnyFreeSpec with Matchers {
   val bytecode = getClass.getResourceAsStream("/mock.wasm").readAllBytes()
 
   "validate bytecode" in {
     val executor = new WASMExecutor
+    val service  = new SimulatorWASMService
 
     val wrongBytecode = Array[Byte](
       0, 14, 21, 1, 2
     )
 
-    executor.validateBytecode(bytecode) shouldBe 0
-    executor.validateBytecode(wrongBytecode) shouldBe 100
+    executor.validateBytecode(bytecode, Array.empty[Byte], service) shouldBe 0
+    service.lastError shouldBe None
+
+    executor.validateBytecode(wrongBytecode, Array.empty[Byte], service) shouldBe 100
+    service.lastError.map(_._1) shouldBe Some(100)
+
+    val simulator = new Simulator(bytecode)
+    simulator.setConfig(VmConfig(maxBytecodeSize = 1))
+    simulator.validateBytecode() shouldBe 119
+    simulator.lastError.map(_._2) shouldBe Some("Executable")
+  }
+
+  "lint bytecode" in {
+    val executor = new WASMExecutor
+    val service  = new SimulatorWASMService
+
+    val result = executor.lintBytecode(bytecode, Array.empty[Byte], service)
+    ByteBuffer.wrap(result).getInt shouldBe 0
+    service.lastError shouldBe None
+
+    val wrongResult = executor.lintBytecode(Array[Byte](0, 14, 21, 1, 2), Array.empty[Byte], service)
+    ByteBuffer.wrap(wrongResult).getInt shouldBe 100
+    service.lastError.map(_._1) shouldBe Some(100)
+
+    val invalidConfig = executor.lintBytecode(bytecode, VmConfig(memoryInitial = 4, memoryMaximum = 2).bytes, service)
+    ByteBuffer.wrap(invalidConfig).getInt shouldBe 121
+
+    val truncatedConfig = executor.lintBytecode(bytecode, Array[Byte](0, 0, 0, 1), service)
+    ByteBuffer.wrap(truncatedConfig).getInt shouldBe 121
+
+    val costs = VmConfig(costs = Seq(HostFunctionCost("env0", "sha256", 10L, 2L)))
+    ByteBuffer.wrap(executor.lintBytecode(bytecode, costs.bytes, service)).getInt shouldBe 0
+
+    val unknownCost = VmConfig(costs = Seq(HostFunctionCost("env0", "unknown", 10L, 2L)))
+    ByteBuffer.wrap(executor.lintBytecode(bytecode, unknownCost.bytes, service)).getInt shouldBe 121
   }
 
   "infinite_loop" in {
@@ -23,6 +59,30 @@ class WASMExecutorSpec extends AnyFreeSpec with Matchers {
     simulator.callMethod("infinite_loop", Array.empty[Byte]) shouldBe 111
   }
 
+  "fuel consumed" in {
+    val simulator = new Simulator(bytecode)
+
+    simulator.callMethod("infinite_loop", Array.empty[Byte]) shouldBe 111
+    val Seq((funcName, depth, fuel)) = simulator.fuelConsumed
+
+    funcName shouldBe "infinite_loop"
+    depth shouldBe 0
+    fuel should (be > 0L and be <= simulator.fuelLimit)
+  }
+
+  "reporter failures" in {
+    val executor = new WASMExecutor
+    val service = new SimulatorWASMService {
+      override def fuelConsumed(contractId: Array[Byte], funcName: String, depth: Int, fuel: Long): Unit =
+        throw new RuntimeException("fuelConsumed failed")
+
+      override def executionFailed(code: Int, category: String, message: String, contractId: Array[Byte], frames: Array[Byte]): Unit =
+        throw new RuntimeException("executionFailed failed")
+    }
+
+    executor.runContract(Array.fill[Byte](32)(1), bytecode, "infinite_loop", Array.empty[Byte], 1024L, Array.empty[Byte], service) shouldBe 111
+  }
+
   "recursion" in {
     val simulator = new Simulator(bytecode)
 